@@ -0,0 +1,1108 @@
+// Generate the language modules ("english", "japanese"...) from "phrases.txt".
+//
+// The data file is checked first. All the problems are reported at once, with the line where
+// they occur, and the build fails. Then, for each language, the file "$OUT_DIR/<module>.rs" is
+// written. It contains one sub-module per phrase key ("greetings", "farewells"...) and the
+// entry of the catalog for the language ("LANGUAGE"). These files are included by the
+// hand-written modules "src/<module>/mod.rs".
+//
+// The phrase keys also become the enum "catalog::Key" ("languages.rs"), and the macro
+// "phrase!()" ("macros.rs") gives the text of a phrase from identifiers that are checked at
+// compile time: "phrase!(en_gb, farewell.polite)".
+//
+// A regional variant ("en-GB") is declared with the module path "<base module>::<name>"
+// ("english::gb"). It only defines the phrases that differ from its base language: the other
+// ones are inherited. Its module is generated inside the module of its base language.
+//
+// An optional language (a language pack) is only compiled with its Cargo feature ("[features]"
+// section). It has no hand-written module: its module is declared by "$OUT_DIR/packs.rs", which
+// is included by "lib.rs". Its phrases are checked whether the feature is enabled or not.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DATA_FILE: &str = "phrases.txt";
+const FORMALITIES: [&str; 2] = ["casual", "polite"];
+
+struct Language {
+    line: usize,
+    tag: String,
+    module: String,
+    name: String,
+}
+
+impl Language {
+    // For a regional variant, return the module of the base language and the name of the
+    // variant module.
+    fn variant(&self) -> Option<(&str, &str)> {
+        self.module.split_once("::")
+    }
+}
+
+struct Key {
+    line: usize,
+    key: String,
+    module: String,
+    title: String,
+}
+
+struct Translation {
+    line: usize,
+    text: String,
+    // Japanese only: the text annotated with the readings of its kanji ("<tag>.ruby" line).
+    ruby: Option<(usize, String)>,
+    // Review metadata ("<tag>.review" line): required for all the languages but the source one.
+    review: Option<(usize, String)>,
+}
+
+// Parsed "<tag>.review" line.
+struct Review {
+    status: String,
+    translator: String,
+    timestamp: String,
+    source_hash: u32,
+}
+
+const STATUSES: [&str; 4] = ["draft", "translated", "reviewed", "approved"];
+
+// A "[<key>.<formality>]" section.
+struct Phrase {
+    line: usize,
+    key: String,
+    formality: String,
+    // Translations, indexed by language tag.
+    texts: BTreeMap<String, Translation>,
+}
+
+#[derive(Default)]
+struct Data {
+    languages: Vec<Language>,
+    // Cargo feature of the optional languages: tag -> (line, feature).
+    features: BTreeMap<String, (usize, String)>,
+    keys: Vec<Key>,
+    phrases: Vec<Phrase>,
+}
+
+#[derive(Default)]
+struct Diagnostics {
+    // The line of each error, "None" for the errors of the whole file.
+    errors: Vec<(Option<usize>, String)>,
+}
+
+impl Diagnostics {
+    fn error(&mut self, line: usize, message: String) {
+        self.errors.push((Some(line), message));
+    }
+
+    fn file_error(&mut self, message: String) {
+        self.errors.push((None, message));
+    }
+}
+
+enum Section {
+    None,
+    Languages,
+    Features,
+    Keys,
+    Phrase(usize),
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", DATA_FILE);
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+
+    let source = fs::read_to_string(DATA_FILE).unwrap_or_else(|error| {
+        eprintln!("error: cannot read \"{}\": {}", DATA_FILE, error);
+        process::exit(1);
+    });
+
+    let mut diagnostics = Diagnostics::default();
+    let data = parse(&source, &mut diagnostics);
+    check(&data, &mut diagnostics);
+    if !diagnostics.errors.is_empty() {
+        diagnostics.errors.sort_by_key(|(line, _)| *line);
+        for (line, message) in &diagnostics.errors {
+            match line {
+                Some(line) => eprintln!("error: {}:{}: {}", DATA_FILE, line, message),
+                None => eprintln!("error: {}: {}", DATA_FILE, message),
+            }
+        }
+        eprintln!("error: {} problem(s) found in \"{}\"", diagnostics.errors.len(), DATA_FILE);
+        process::exit(1);
+    }
+
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    for language in data.languages.iter().filter(|language| language.variant().is_none()) {
+        write(&out_dir, &format!("{}.rs", language.module), &generate_language(&data, language));
+    }
+    write(&out_dir, "languages.rs", &generate_languages(&data));
+    write(&out_dir, "packs.rs", &generate_packs(&data));
+    write(&out_dir, "macros.rs", &generate_macros(&data));
+
+    build_identity();
+}
+
+// Pass the build time and the git commit to the crate (see "src/info.rs").
+fn build_identity() {
+    // For reproducible builds, "SOURCE_DATE_EPOCH" replaces the current time.
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let seconds = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0)
+        });
+    println!("cargo:rustc-env=MODULES_BUILD_TIMESTAMP={}", timestamp(seconds));
+
+    let git = |args: &[&str]| -> Option<String> {
+        let output = Command::new("git").args(args).output().ok()?;
+        let text = String::from_utf8(output.stdout).ok()?;
+        output.status.success().then(|| text.trim().to_string())
+    };
+    let hash = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_default();
+    println!("cargo:rustc-env=MODULES_GIT_HASH={}", hash);
+    // Run again when HEAD moves, so that the hash stays up to date. Missing files would make
+    // Cargo run this script at every build, hence the checks.
+    for path in [git(&["rev-parse", "--git-path", "HEAD"]), git(&["symbolic-ref", "-q", "HEAD"])
+        .and_then(|reference| git(&["rev-parse", "--git-path", &reference]))]
+    .into_iter()
+    .flatten()
+    {
+        if Path::new(&path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
+
+// Format a number of seconds since the Unix epoch as "YYYY-MM-DDTHH:MM:SSZ".
+fn timestamp(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let time = seconds % 86_400;
+    // Conversion of a number of days into a civil date (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+fn write(out_dir: &str, name: &str, contents: &str) {
+    let path = Path::new(out_dir).join(name);
+    fs::write(&path, contents).unwrap_or_else(|error| {
+        eprintln!("error: cannot write \"{}\": {}", path.display(), error);
+        process::exit(1);
+    });
+}
+
+fn parse(source: &str, diagnostics: &mut Diagnostics) -> Data {
+    let mut data = Data::default();
+    let mut section = Section::None;
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = match name.trim() {
+                "languages" => Section::Languages,
+                "features" => Section::Features,
+                "keys" => Section::Keys,
+                name => match name.rsplit_once('.') {
+                    Some((key, formality)) => {
+                        data.phrases.push(Phrase {
+                            line: number,
+                            key: key.to_string(),
+                            formality: formality.to_string(),
+                            texts: BTreeMap::new(),
+                        });
+                        Section::Phrase(data.phrases.len() - 1)
+                    }
+                    None => {
+                        diagnostics.error(
+                            number,
+                            format!(
+                                "invalid section \"[{}]\" (expected \"[languages]\", \"[features]\", \"[keys]\" or \"[<key>.<formality>]\")",
+                                name
+                            ),
+                        );
+                        Section::None
+                    }
+                },
+            };
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once('=') else {
+            diagnostics.error(number, format!("expected \"<name> = <value>\", found \"{}\"", line));
+            continue;
+        };
+        let (name, value) = (name.trim(), value.trim());
+        match section {
+            Section::None => {
+                diagnostics.error(number, format!("\"{}\" does not belong to any section", name));
+            }
+            Section::Languages => match value.split_once(' ') {
+                Some((module, language_name)) => data.languages.push(Language {
+                    line: number,
+                    tag: name.to_string(),
+                    module: module.to_string(),
+                    name: language_name.trim().to_string(),
+                }),
+                None => diagnostics.error(
+                    number,
+                    format!("language \"{}\": expected \"<module> <English name>\"", name),
+                ),
+            },
+            Section::Features => {
+                if let Some((line, _)) = data.features.get(name) {
+                    diagnostics.error(
+                        number,
+                        format!("duplicate feature for language \"{}\" (first declared at line {})", name, line),
+                    );
+                    continue;
+                }
+                data.features.insert(name.to_string(), (number, value.to_string()));
+            }
+            Section::Keys => match value.split_once(' ') {
+                Some((module, title)) => data.keys.push(Key {
+                    line: number,
+                    key: name.to_string(),
+                    module: module.to_string(),
+                    title: title.trim().to_string(),
+                }),
+                None => diagnostics.error(number, format!("key \"{}\": expected \"<module> <title>\"", name)),
+            },
+            Section::Phrase(phrase) => {
+                let phrase = &mut data.phrases[phrase];
+                // A missing translation is written by omitting its line, not by an empty text. An
+                // empty translation is still recorded, so that it is not reported as missing too.
+                if value.is_empty() {
+                    diagnostics.error(
+                        number,
+                        format!("empty value for \"{}\" of \"{}.{}\"", name, phrase.key, phrase.formality),
+                    );
+                    if name.contains('.') {
+                        continue;
+                    }
+                }
+                if let Some((tag, attribute)) = name.split_once('.') {
+                    let Some(translation) = phrase.texts.get_mut(tag) else {
+                        diagnostics.error(
+                            number,
+                            format!("\"{}\" must follow the translation \"{}\" of \"{}.{}\"", name, tag, phrase.key, phrase.formality),
+                        );
+                        continue;
+                    };
+                    let slot = match attribute {
+                        "ruby" => &mut translation.ruby,
+                        "review" => &mut translation.review,
+                        _ => {
+                            diagnostics.error(
+                                number,
+                                format!("unknown attribute \"{}\" (expected \"ruby\" or \"review\")", name),
+                            );
+                            continue;
+                        }
+                    };
+                    match slot {
+                        Some((line, _)) => diagnostics.error(
+                            number,
+                            format!("duplicate \"{}\" for \"{}.{}\" (first defined at line {})", name, phrase.key, phrase.formality, line),
+                        ),
+                        None => *slot = Some((number, value.to_string())),
+                    }
+                    continue;
+                }
+                if let Some(previous) = phrase.texts.get(name) {
+                    diagnostics.error(
+                        number,
+                        format!(
+                            "duplicate translation \"{}\" for \"{}.{}\" (first defined at line {})",
+                            name, phrase.key, phrase.formality, previous.line
+                        ),
+                    );
+                    continue;
+                }
+                phrase.texts.insert(
+                    name.to_string(),
+                    Translation {
+                        line: number,
+                        text: value.to_string(),
+                        ruby: None,
+                        review: None,
+                    },
+                );
+            }
+        }
+    }
+    data
+}
+
+fn check(data: &Data, diagnostics: &mut Diagnostics) {
+    if data.languages.is_empty() {
+        diagnostics.file_error("no language is declared".to_string());
+        return;
+    }
+
+    // Declarations.
+    let mut tags: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut modules: BTreeMap<&str, usize> = BTreeMap::new();
+    for language in &data.languages {
+        if let Some(line) = tags.insert(&language.tag, language.line) {
+            diagnostics.error(
+                language.line,
+                format!("duplicate language \"{}\" (first declared at line {})", language.tag, line),
+            );
+        }
+        if let Some(line) = modules.insert(&language.module, language.line) {
+            diagnostics.error(
+                language.line,
+                format!("duplicate module \"{}\" (first declared at line {})", language.module, line),
+            );
+        }
+        if !is_tag(&language.tag) {
+            diagnostics.error(language.line, format!("invalid language tag \"{}\"", language.tag));
+        }
+        match language.variant() {
+            None => {
+                if !is_identifier(&language.module) {
+                    diagnostics.error(language.line, format!("invalid module name \"{}\"", language.module));
+                }
+            }
+            Some((base, name)) => {
+                if !is_identifier(name) {
+                    diagnostics.error(language.line, format!("invalid module name \"{}\"", language.module));
+                }
+                match data.languages.iter().find(|other| other.module == base) {
+                    None => diagnostics.error(
+                        language.line,
+                        format!("variant \"{}\": undeclared base module \"{}\"", language.tag, base),
+                    ),
+                    Some(parent) => {
+                        if !language.tag.starts_with(&format!("{}-", parent.tag)) {
+                            diagnostics.error(
+                                language.line,
+                                format!(
+                                    "variant \"{}\": the tag must start with the tag of its base language (\"{}-\")",
+                                    language.tag, parent.tag
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    if data.languages[0].variant().is_some() {
+        diagnostics.error(
+            data.languages[0].line,
+            format!("the source language \"{}\" cannot be a regional variant", data.languages[0].tag),
+        );
+    }
+    let manifest = fs::read_to_string("Cargo.toml").unwrap_or_default();
+    for (tag, (line, feature)) in &data.features {
+        match data.languages.iter().find(|language| &language.tag == tag) {
+            None => diagnostics.error(*line, format!("feature \"{}\": undeclared language \"{}\"", feature, tag)),
+            Some(language) if language.variant().is_some() => diagnostics.error(
+                *line,
+                format!("feature \"{}\": the regional variant \"{}\" follows its base language", feature, tag),
+            ),
+            Some(language) if std::ptr::eq(language, &data.languages[0]) => diagnostics.error(
+                *line,
+                format!("feature \"{}\": the source language \"{}\" cannot be optional", feature, tag),
+            ),
+            Some(_) => {}
+        }
+        if !manifest.lines().any(|entry| entry.split('=').next().map(str::trim) == Some(feature.as_str())) {
+            diagnostics.error(*line, format!("feature \"{}\" is not declared in \"Cargo.toml\"", feature));
+        }
+    }
+
+    let mut keys: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut key_modules: BTreeMap<&str, usize> = BTreeMap::new();
+    for key in &data.keys {
+        if let Some(line) = keys.insert(&key.key, key.line) {
+            diagnostics.error(key.line, format!("duplicate key \"{}\" (first declared at line {})", key.key, line));
+        }
+        if let Some(line) = key_modules.insert(&key.module, key.line) {
+            diagnostics.error(
+                key.line,
+                format!("duplicate module \"{}\" (first declared at line {})", key.module, line),
+            );
+        }
+        if !is_identifier(&key.key) {
+            diagnostics.error(key.line, format!("invalid key \"{}\"", key.key));
+        }
+        if !is_identifier(&key.module) {
+            diagnostics.error(key.line, format!("invalid module name \"{}\"", key.module));
+        }
+    }
+
+    // Phrases.
+    let source = &data.languages[0];
+    let mut defined: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+    for phrase in &data.phrases {
+        let id = format!("{}.{}", phrase.key, phrase.formality);
+        if !keys.contains_key(phrase.key.as_str()) {
+            diagnostics.error(phrase.line, format!("\"[{}]\": undeclared key \"{}\"", id, phrase.key));
+        }
+        if !FORMALITIES.contains(&phrase.formality.as_str()) {
+            diagnostics.error(
+                phrase.line,
+                format!(
+                    "\"[{}]\": unknown formality \"{}\" (expected one of: {})",
+                    id,
+                    phrase.formality,
+                    FORMALITIES.join(", ")
+                ),
+            );
+        }
+        if let Some(line) = defined.insert((&phrase.key, &phrase.formality), phrase.line) {
+            diagnostics.error(phrase.line, format!("duplicate phrase \"[{}]\" (first defined at line {})", id, line));
+        }
+        // The source language defines every phrase. The other languages may lack a translation:
+        // the source text is used instead, and the build warns ("modules check <language>" lists
+        // them). Regional variants only define the phrases that differ from their base language.
+        if !phrase.texts.contains_key(&source.tag) {
+            diagnostics.error(
+                phrase.line,
+                format!("\"[{}]\": missing text for the source language \"{}\"", id, source.tag),
+            );
+        }
+        for language in data.languages.iter().skip(1).filter(|language| language.variant().is_none()) {
+            if !phrase.texts.contains_key(&language.tag) {
+                println!(
+                    "cargo:warning={}:{}: \"[{}]\": no translation for language \"{}\", the source text is used",
+                    DATA_FILE, phrase.line, id, language.tag
+                );
+            }
+        }
+
+        let source_placeholders = phrase
+            .texts
+            .get(&source.tag)
+            .and_then(|translation| placeholders(&translation.text).ok());
+        for (tag, translation) in &phrase.texts {
+            if !tags.contains_key(tag.as_str()) {
+                diagnostics.error(translation.line, format!("\"[{}]\": undeclared language \"{}\"", id, tag));
+            }
+            check_ruby(&id, tag, translation, diagnostics);
+            check_review(&id, tag, translation, phrase.texts.get(&source.tag), source, diagnostics);
+            match placeholders(&translation.text) {
+                Err(message) => diagnostics.error(
+                    translation.line,
+                    format!("\"[{}]\", language \"{}\": {}", id, tag, message),
+                ),
+                Ok(found) => {
+                    if let Some(expected) = &source_placeholders {
+                        if &found != expected {
+                            diagnostics.error(
+                                translation.line,
+                                format!(
+                                    "\"[{}]\", language \"{}\": placeholders {{{}}} do not match the placeholders of \"{}\" {{{}}}",
+                                    id,
+                                    tag,
+                                    join(&found),
+                                    source.tag,
+                                    join(expected)
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+    for key in &data.keys {
+        for formality in FORMALITIES {
+            if !defined.contains_key(&(key.key.as_str(), formality)) {
+                diagnostics.error(key.line, format!("key \"{}\": missing phrase \"[{}.{}]\"", key.key, key.key, formality));
+            }
+        }
+    }
+}
+
+// Japanese phrases must give the reading of their kanji, as "[base|reading]" annotations:
+//
+//     ja = {name}様、ようこそ。
+//     ja.ruby = {name}[様|さま]、ようこそ。
+//
+// The annotated text, without its readings, must be the phrase. Readings must be kana, and
+// replacing the annotated kanji by their readings (the kana rendering) must leave no kanji.
+fn check_ruby(id: &str, tag: &str, translation: &Translation, diagnostics: &mut Diagnostics) {
+    let japanese = tag == "ja" || tag.starts_with("ja-");
+    let Some((line, ruby)) = &translation.ruby else {
+        if japanese {
+            if let Some(kanji) = translation.text.chars().find(|c| is_kanji(*c)) {
+                diagnostics.error(
+                    translation.line,
+                    format!("\"[{}]\", language \"{}\": missing reading of \"{}\" (add a \"{}.ruby\" line)", id, tag, kanji, tag),
+                );
+            }
+        }
+        return;
+    };
+    if !japanese {
+        diagnostics.error(*line, format!("\"[{}]\": readings are only supported for Japanese, not \"{}\"", id, tag));
+        return;
+    }
+    let segments = match ruby_segments(ruby) {
+        Ok(segments) => segments,
+        Err(message) => {
+            diagnostics.error(*line, format!("\"[{}]\", language \"{}\": {}", id, tag, message));
+            return;
+        }
+    };
+    let base: String = segments.iter().map(|(base, _)| base.as_str()).collect();
+    if base != translation.text {
+        diagnostics.error(
+            *line,
+            format!("\"[{}]\", language \"{}\": the annotated text \"{}\" differs from the phrase \"{}\"", id, tag, base, translation.text),
+        );
+    }
+    for (base, reading) in &segments {
+        match reading {
+            Some(reading) => {
+                if let Some(c) = reading.chars().find(|c| !is_kana(*c)) {
+                    diagnostics.error(
+                        *line,
+                        format!("\"[{}]\", language \"{}\": the reading \"{}\" of \"{}\" contains \"{}\", which is not kana", id, tag, reading, base, c),
+                    );
+                }
+            }
+            None => {
+                if let Some(kanji) = base.chars().find(|c| is_kanji(*c)) {
+                    diagnostics.error(
+                        *line,
+                        format!("\"[{}]\", language \"{}\": the kana rendering still contains \"{}\", which has no reading", id, tag, kanji),
+                    );
+                }
+            }
+        }
+    }
+}
+
+// The translations are reviewed: each one gives its status, its translator, the date of the
+// last change and the hash of the source text it was translated from:
+//
+//     ja.review = approved | Keiko Tanaka | 2022-03-21 | 575b1d3a
+//
+// When the source text changes, its hash changes: the translation is stale. Stale translations
+// do not stop the build, they are reported as warnings.
+fn check_review(
+    id: &str,
+    tag: &str,
+    translation: &Translation,
+    source_translation: Option<&Translation>,
+    source: &Language,
+    diagnostics: &mut Diagnostics,
+) {
+    let Some((line, review)) = &translation.review else {
+        if tag != source.tag {
+            diagnostics.error(
+                translation.line,
+                format!("\"[{}]\", language \"{}\": missing review metadata (add a \"{}.review\" line)", id, tag, tag),
+            );
+        }
+        return;
+    };
+    if tag == source.tag {
+        diagnostics.error(*line, format!("\"[{}]\": the source language \"{}\" is not reviewed", id, tag));
+        return;
+    }
+    match parse_review(review) {
+        Err(message) => diagnostics.error(*line, format!("\"[{}]\", language \"{}\": {}", id, tag, message)),
+        Ok(review) => {
+            if let Some(source_translation) = source_translation {
+                let expected = source_hash(&source_translation.text);
+                if review.source_hash != expected {
+                    println!(
+                        "cargo:warning={}:{}: \"[{}]\", language \"{}\": stale translation, the source text changed (its hash is now {:08x})",
+                        DATA_FILE, line, id, tag, expected
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn parse_review(text: &str) -> Result<Review, String> {
+    let fields: Vec<&str> = text.split('|').map(str::trim).collect();
+    let [status, translator, timestamp, hash] = fields[..] else {
+        return Err("expected \"<status> | <translator> | <date> | <source hash>\"".to_string());
+    };
+    if !STATUSES.contains(&status) {
+        return Err(format!("unknown status \"{}\" (expected one of: {})", status, STATUSES.join(", ")));
+    }
+    if translator.is_empty() {
+        return Err("missing translator".to_string());
+    }
+    if !is_timestamp(timestamp) {
+        return Err(format!("invalid date \"{}\" (expected \"YYYY-MM-DD\" or \"YYYY-MM-DDTHH:MM:SSZ\")", timestamp));
+    }
+    let source_hash = u32::from_str_radix(hash, 16)
+        .ok()
+        .filter(|_| hash.len() == 8)
+        .ok_or_else(|| format!("invalid source hash \"{}\" (expected 8 hexadecimal digits)", hash))?;
+    Ok(Review {
+        status: status.to_string(),
+        translator: translator.to_string(),
+        timestamp: timestamp.to_string(),
+        source_hash,
+    })
+}
+
+fn is_timestamp(text: &str) -> bool {
+    let pattern = |text: &str, pattern: &str| {
+        text.len() == pattern.len()
+            && text
+                .bytes()
+                .zip(pattern.bytes())
+                .all(|(c, p)| if p == b'9' { c.is_ascii_digit() } else { c == p })
+    };
+    pattern(text, "9999-99-99") || pattern(text, "9999-99-99T99:99:99Z")
+}
+
+// FNV-1a (32 bits), as computed by "review::source_hash()".
+fn source_hash(text: &str) -> u32 {
+    text.bytes()
+        .fold(0x811c_9dc5, |hash: u32, byte| (hash ^ byte as u32).wrapping_mul(0x0100_0193))
+}
+
+// Split an annotated text into (base, reading) segments.
+fn ruby_segments(ruby: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut segments = Vec::new();
+    let mut rest = ruby;
+    while let Some(start) = rest.find('[') {
+        if start > 0 {
+            segments.push((rest[..start].to_string(), None));
+        }
+        let end = rest[start..]
+            .find(']')
+            .map(|end| start + end)
+            .ok_or_else(|| format!("unterminated annotation \"{}\"", &rest[start..]))?;
+        let annotation = &rest[start + 1..end];
+        match annotation.split_once('|') {
+            Some((base, reading)) if !base.is_empty() && !reading.is_empty() && !annotation.contains('[') => {
+                segments.push((base.to_string(), Some(reading.to_string())))
+            }
+            _ => return Err(format!("invalid annotation \"[{}]\" (expected \"[base|reading]\")", annotation)),
+        }
+        rest = &rest[end + 1..];
+    }
+    if rest.contains(']') {
+        return Err("unmatched \"]\"".to_string());
+    }
+    if !rest.is_empty() {
+        segments.push((rest.to_string(), None));
+    }
+    Ok(segments)
+}
+
+fn is_kanji(c: char) -> bool {
+    matches!(c, '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}' | '々')
+}
+
+// Hiragana, katakana and the prolonged sound mark.
+fn is_kana(c: char) -> bool {
+    matches!(c, '\u{3041}'..='\u{3096}' | '\u{309d}'..='\u{309f}' | '\u{30a1}'..='\u{30fa}' | '\u{30fc}'..='\u{30ff}')
+}
+
+// Return the names of the placeholders used by a text.
+fn placeholders(text: &str) -> Result<BTreeSet<String>, String> {
+    let mut names = BTreeSet::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unterminated placeholder \"{{{}\"", name)),
+                    }
+                }
+                if !is_identifier(&name) {
+                    return Err(format!("invalid placeholder name \"{{{}}}\"", name));
+                }
+                names.insert(name);
+            }
+            '}' => return Err("unmatched \"}\" (write \"}}\" for a literal brace)".to_string()),
+            _ => {}
+        }
+    }
+    Ok(names)
+}
+
+fn join(names: &BTreeSet<String>) -> String {
+    names.iter().cloned().collect::<Vec<String>>().join(", ")
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn is_tag(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .split('-')
+            .all(|subtag| !subtag.is_empty() && subtag.len() <= 8 && subtag.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+fn generate_language(data: &Data, language: &Language) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by \"build.rs\" from \"{}\". Do not edit.", DATA_FILE).unwrap();
+    generate_phrases(&mut out, data, language, None, "");
+    for variant in data.languages.iter() {
+        if let Some((base, name)) = variant.variant() {
+            if base == language.module {
+                writeln!(out).unwrap();
+                writeln!(
+                    out,
+                    "// Regional variant \"{}\": only the phrases that differ from \"{}\" are defined here.",
+                    variant.tag, language.tag
+                )
+                .unwrap();
+                writeln!(out, "pub mod {} {{", name).unwrap();
+                generate_phrases(&mut out, data, variant, Some(language), "    ");
+                writeln!(out, "}}").unwrap();
+            }
+        }
+    }
+    out
+}
+
+// Write the sub-modules of a language (one per key) and its entry in the catalog. The phrases
+// that a regional variant does not define are taken from its base language ("parent"), the ones
+// that another language lacks from the source language.
+fn generate_phrases(out: &mut String, data: &Data, language: &Language, parent: Option<&Language>, indent: &str) {
+    // Return the translation of a phrase, if the language defines it.
+    let translation = |key: &str, formality: &str| -> Option<&Translation> {
+        let phrase = data
+            .phrases
+            .iter()
+            .find(|phrase| phrase.key == key && phrase.formality == formality)
+            .expect("checked by check()");
+        phrase.texts.get(&language.tag)
+    };
+    let text = |key: &str, formality: &str| translation(key, formality).map(|translation| translation.text.as_str());
+
+    for key in &data.keys {
+        writeln!(out).unwrap();
+        writeln!(out, "{}pub mod {} {{", indent, key.module).unwrap();
+        writeln!(out, "{}    use crate::catalog::Formality;", indent).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "{}    pub const KEY: &str = {:?};", indent, key.key).unwrap();
+        for formality in FORMALITIES {
+            match (text(&key.key, formality), parent) {
+                (Some(text), _) => writeln!(out, "{}    pub const {}: &str = {:?};", indent, formality.to_uppercase(), text),
+                (None, Some(_)) => writeln!(
+                    out,
+                    "{}    pub const {}: &str = super::super::{}::{};",
+                    indent,
+                    formality.to_uppercase(),
+                    key.module,
+                    formality.to_uppercase()
+                ),
+                (None, None) => writeln!(
+                    out,
+                    "{}    pub const {}: &str = crate::{}::{}::{};",
+                    indent,
+                    formality.to_uppercase(),
+                    data.languages[0].module,
+                    key.module,
+                    formality.to_uppercase()
+                ),
+            }
+            .unwrap();
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "{}    pub fn text(formality: Formality) -> &'static str {{", indent).unwrap();
+        writeln!(out, "{}        match formality {{", indent).unwrap();
+        for formality in FORMALITIES {
+            writeln!(out, "{}            Formality::{} => {},", indent, enum_variant(formality), formality.to_uppercase()).unwrap();
+        }
+        writeln!(out, "{}        }}", indent).unwrap();
+        writeln!(out, "{}    }}", indent).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "{}    #[cfg(feature = \"std\")]", indent).unwrap();
+        writeln!(out, "{}    pub fn say() {{", indent).unwrap();
+        writeln!(out, "{}        println!({:?})", indent, format!("{} in {}!", key.title, language.name)).unwrap();
+        writeln!(out, "{}    }}", indent).unwrap();
+        writeln!(out, "{}}}", indent).unwrap();
+    }
+
+    writeln!(out).unwrap();
+    writeln!(out, "{}// The entry of the catalog for this language.", indent).unwrap();
+    writeln!(out, "{}pub static LANGUAGE: crate::catalog::Language = crate::catalog::Language {{", indent).unwrap();
+    writeln!(out, "{}    tag: {:?},", indent, language.tag).unwrap();
+    writeln!(out, "{}    name: {:?},", indent, language.name).unwrap();
+    writeln!(out, "{}    index: {},", indent, language_index(data, language)).unwrap();
+    match parent {
+        Some(_) => writeln!(out, "{}    parent: Some(&super::LANGUAGE),", indent).unwrap(),
+        None => writeln!(out, "{}    parent: None,", indent).unwrap(),
+    }
+    writeln!(out, "{}    phrases: &[", indent).unwrap();
+    for key in &data.keys {
+        for formality in FORMALITIES.iter().filter(|formality| text(&key.key, formality).is_some()) {
+            writeln!(
+                out,
+                "{}        crate::catalog::Phrase {{ key: {}::KEY, checked_key: crate::catalog::Key::{}, formality: crate::catalog::Formality::{}, text: {}::{}, ruby: {}, review: {} }},",
+                indent,
+                key.module,
+                enum_variant(&key.key),
+                enum_variant(formality),
+                key.module,
+                formality.to_uppercase(),
+                match translation(&key.key, formality).and_then(|translation| translation.ruby.as_ref()) {
+                    Some((_, ruby)) => format!("Some({:?})", ruby),
+                    None => "None".to_string(),
+                },
+                match translation(&key.key, formality)
+                    .and_then(|translation| translation.review.as_ref())
+                    .and_then(|(_, review)| parse_review(review).ok())
+                {
+                    Some(review) => format!(
+                        "Some(crate::review::Review {{ status: crate::review::Status::{}, translator: {:?}, timestamp: {:?}, source_hash: 0x{:08x} }})",
+                        enum_variant(&review.status),
+                        review.translator,
+                        review.timestamp,
+                        review.source_hash
+                    ),
+                    None => "None".to_string(),
+                }
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "{}    ],", indent).unwrap();
+    writeln!(out, "{}}};", indent).unwrap();
+}
+
+fn generate_languages(data: &Data) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by \"build.rs\" from \"{}\". Do not edit.", DATA_FILE).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// All the languages compiled into the crate. The first one is the source language.").unwrap();
+    writeln!(out, "pub static LANGUAGES: &[&Language] = &[").unwrap();
+    let mut count = 0;
+    for language in &data.languages {
+        if let Some(feature) = feature(data, language) {
+            writeln!(out, "    #[cfg(feature = {:?})]", feature).unwrap();
+        }
+        writeln!(out, "    &crate::{}::LANGUAGE,", language.module).unwrap();
+        count += enabled(data, language) as usize;
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// Number of languages, that is \"LANGUAGES.len()\" known at compile time.").unwrap();
+    writeln!(out, "pub const LANGUAGE_COUNT: usize = {};", count).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// All the phrase keys, in the order of \"{}\".", DATA_FILE).unwrap();
+    writeln!(out, "pub const KEYS: &[&str] = &[").unwrap();
+    for key in &data.keys {
+        writeln!(out, "    {:?},", key.key).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// The phrase keys, checked at compile time.").unwrap();
+    writeln!(out, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]").unwrap();
+    writeln!(out, "pub enum Key {{").unwrap();
+    for key in &data.keys {
+        writeln!(out, "    {},", enum_variant(&key.key)).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "impl Key {{").unwrap();
+    writeln!(out, "    pub const ALL: [Key; {}] = [", data.keys.len()).unwrap();
+    for key in &data.keys {
+        writeln!(out, "        Key::{},", enum_variant(&key.key)).unwrap();
+    }
+    writeln!(out, "    ];").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    pub fn as_str(self) -> &'static str {{").unwrap();
+    writeln!(out, "        match self {{").unwrap();
+    for key in &data.keys {
+        writeln!(out, "            Key::{} => {:?},", enum_variant(&key.key), key.key).unwrap();
+    }
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "    pub fn parse(text: &str) -> Option<Key> {{").unwrap();
+    writeln!(out, "        match text {{").unwrap();
+    for key in &data.keys {
+        writeln!(out, "            {:?} => Some(Key::{}),", key.key, enum_variant(&key.key)).unwrap();
+    }
+    writeln!(out, "            _ => None,").unwrap();
+    writeln!(out, "        }}").unwrap();
+    writeln!(out, "    }}").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// Define the macro "phrase!()": one rule per language and phrase, which expands to the constant
+// of the generated module. The other rules expand to a compilation error that explains the
+// problem: unknown phrase, unknown language, or language pack whose feature is disabled.
+fn generate_macros(data: &Data) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by \"build.rs\" from \"{}\". Do not edit.", DATA_FILE).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "// The text of a phrase, checked at compile time: \"phrase!(ja, farewell.polite)\".").unwrap();
+    writeln!(out, "// The language is its tag, in lowercase, with \"_\" instead of \"-\" (\"en_gb\" for \"en-GB\").").unwrap();
+    writeln!(out, "#[macro_export]").unwrap();
+    writeln!(out, "macro_rules! phrase {{").unwrap();
+    let phrases: Vec<String> = data
+        .phrases
+        .iter()
+        .map(|phrase| format!("{}.{}", phrase.key, phrase.formality))
+        .collect();
+    for language in &data.languages {
+        let name = macro_language(language);
+        if !enabled(data, language) {
+            writeln!(out, "    ({}, $key:ident . $formality:ident) => {{", name).unwrap();
+            writeln!(
+                out,
+                "        compile_error!({:?})",
+                format!(
+                    "the language \"{}\" requires the feature \"{}\"",
+                    language.tag,
+                    feature(data, language).expect("only optional languages are disabled")
+                )
+            )
+            .unwrap();
+            writeln!(out, "    }};").unwrap();
+            continue;
+        }
+        for phrase in &data.phrases {
+            let key = data.keys.iter().find(|key| key.key == phrase.key).expect("checked by check()");
+            writeln!(
+                out,
+                "    ({}, {}.{}) => {{ $crate::{}::{}::{} }};",
+                name,
+                phrase.key,
+                phrase.formality,
+                language.module,
+                key.module,
+                phrase.formality.to_uppercase()
+            )
+            .unwrap();
+        }
+        writeln!(out, "    ({}, $key:ident . $formality:ident) => {{", name).unwrap();
+        writeln!(out, "        compile_error!(concat!(").unwrap();
+        writeln!(out, "            \"unknown phrase \\\"\",").unwrap();
+        writeln!(out, "            stringify!($key),").unwrap();
+        writeln!(out, "            \".\",").unwrap();
+        writeln!(out, "            stringify!($formality),").unwrap();
+        writeln!(out, "            {:?}", format!("\" (known phrases: {})", phrases.join(", "))).unwrap();
+        writeln!(out, "        ))").unwrap();
+        writeln!(out, "    }};").unwrap();
+    }
+    let languages: Vec<String> = data.languages.iter().map(macro_language).collect();
+    writeln!(out, "    ($language:ident, $key:ident . $formality:ident) => {{").unwrap();
+    writeln!(out, "        compile_error!(concat!(").unwrap();
+    writeln!(out, "            \"unknown language \\\"\",").unwrap();
+    writeln!(out, "            stringify!($language),").unwrap();
+    writeln!(out, "            {:?}", format!("\" (known languages: {})", languages.join(", "))).unwrap();
+    writeln!(out, "        ))").unwrap();
+    writeln!(out, "    }};").unwrap();
+    writeln!(out, "    ($($tokens:tt)*) => {{").unwrap();
+    writeln!(out, "        compile_error!(\"usage: phrase!(<language>, <key>.<formality>)\")").unwrap();
+    writeln!(out, "    }};").unwrap();
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+// The name of a language in "phrase!()": "en-GB" is "en_gb".
+fn macro_language(language: &Language) -> String {
+    language.tag.to_lowercase().replace('-', "_")
+}
+
+// Declare the modules of the optional languages.
+fn generate_packs(data: &Data) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by \"build.rs\" from \"{}\". Do not edit.", DATA_FILE).unwrap();
+    for language in data.languages.iter().filter(|language| language.variant().is_none()) {
+        if let Some(feature) = feature(data, language) {
+            writeln!(out).unwrap();
+            writeln!(out, "// {} (feature \"{}\").", language.name, feature).unwrap();
+            writeln!(out, "#[cfg(feature = {:?})]", feature).unwrap();
+            writeln!(out, "pub mod {} {{", language.module).unwrap();
+            writeln!(out, "    include!(concat!(env!(\"OUT_DIR\"), \"/{}.rs\"));", language.module).unwrap();
+            writeln!(out, "}}").unwrap();
+        }
+    }
+    out
+}
+
+// The Cargo feature of an optional language (or of the base language of a regional variant).
+fn feature<'a>(data: &'a Data, language: &Language) -> Option<&'a str> {
+    let base = match language.variant() {
+        Some((base, _)) => data.languages.iter().find(|other| other.module == base)?,
+        None => language,
+    };
+    data.features.get(&base.tag).map(|(_, feature)| feature.as_str())
+}
+
+// The position of a language in "LANGUAGES": the optional languages whose feature is disabled
+// are not there.
+fn language_index(data: &Data, language: &Language) -> usize {
+    data.languages
+        .iter()
+        .take_while(|other| other.tag != language.tag)
+        .filter(|other| enabled(data, other))
+        .count()
+}
+
+fn enabled(data: &Data, language: &Language) -> bool {
+    match feature(data, language) {
+        Some(feature) => {
+            let variable = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+            env::var_os(variable).is_some()
+        }
+        None => true,
+    }
+}
+
+// "polite" -> "Polite", "good_night" -> "GoodNight".
+fn enum_variant(name: &str) -> String {
+    name.split('_')
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars.next().into_iter().flat_map(char::to_uppercase).chain(chars)
+        })
+        .collect()
+}
@@ -0,0 +1,608 @@
+// Batch rendering: many (phrase, language, arguments) records at once.
+//
+// The records are read from a CSV file or a JSON Lines file:
+//
+// - CSV: a header line names the columns. The columns "key" ("welcome.polite") and "language"
+//   are required, the other ones are the placeholder values ("name"...). An empty cell gives no
+//   value.
+//
+//       key,language,name
+//       welcome.polite,ja,Yuki
+//
+// - JSON Lines: one object per line, with the placeholder values in "args". Numbers are written
+//   in the conventions of the language ("1,234" in English, "1.234" in German).
+//
+//       {"key": "welcome.polite", "language": "ja", "args": {"name": "Yuki"}}
+//
+// Each record gives one result, in the same format: the line of the record, its key and
+// language, and either the rendered text or an error. A bad record (unknown phrase, missing
+// placeholder value, malformed line...) only fails its own result.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::catalog;
+use crate::format::Conventions;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+impl Format {
+    pub fn parse(text: &str) -> Option<Format> {
+        match text {
+            "csv" => Some(Format::Csv),
+            "jsonl" | "ndjson" => Some(Format::JsonLines),
+            _ => None,
+        }
+    }
+
+    // The format of a file, from its extension.
+    pub fn from_path(path: &str) -> Option<Format> {
+        Format::parse(path.rsplit_once('.')?.1)
+    }
+}
+
+// A placeholder value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arg {
+    Text(String),
+    Integer(i64),
+    // The value and its number of decimal digits ("2.50" is (2.5, 2)).
+    Decimal(f64, usize),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    // Line of the record in the input, from 1.
+    pub line: usize,
+    // Phrase identifier: "welcome.polite".
+    pub key: String,
+    pub language: String,
+    pub args: Vec<(String, Arg)>,
+}
+
+// Result of one record.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcome {
+    pub line: usize,
+    pub key: String,
+    pub language: String,
+    pub result: Result<String, String>,
+}
+
+// Number of records read, and of records that failed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub records: usize,
+    pub errors: usize,
+}
+
+// Render a record through the catalog.
+pub fn render(record: &Record) -> Result<String, String> {
+    let language = catalog::language(&record.language)
+        .ok_or_else(|| format!("unknown language \"{}\"", record.language))?;
+    let (key, formality) = catalog::parse_id(&record.key)
+        .ok_or_else(|| format!("invalid phrase \"{}\" (expected \"<key>.<formality>\")", record.key))?;
+    let text = catalog::lookup(language.tag, key, formality)
+        .ok_or_else(|| format!("unknown phrase \"{}\"", record.key))?;
+    for placeholder in catalog::placeholders(text) {
+        if !record.args.iter().any(|(name, _)| name == placeholder) {
+            return Err(format!("no value for the placeholder \"{{{}}}\"", placeholder));
+        }
+    }
+
+    struct Value<'a>(&'a Arg, Conventions);
+    impl fmt::Display for Value<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match *self.0 {
+                Arg::Text(ref text) => f.write_str(text),
+                Arg::Integer(number) => write!(f, "{}", self.1.number(number)),
+                Arg::Decimal(number, digits) => write!(f, "{}", self.1.decimal(number, digits)),
+            }
+        }
+    }
+    let conventions = Conventions::of(language);
+    let values: Vec<Value> = record.args.iter().map(|(_, arg)| Value(arg, conventions)).collect();
+    let args: Vec<(&str, &dyn fmt::Display)> = record
+        .args
+        .iter()
+        .zip(&values)
+        .map(|((name, _), value)| (name.as_str(), value as &dyn fmt::Display))
+        .collect();
+    let mut out = String::new();
+    catalog::render(&mut out, text, &args).expect("writing into a String does not fail");
+    Ok(out)
+}
+
+// Render all the records of "input" and write the results into "output". Only I/O errors stop
+// the run.
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W, format: Format) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    if format == Format::Csv {
+        writeln!(output, "line,key,language,text,error")?;
+    }
+    let mut emit = |outcome: Outcome| -> io::Result<()> {
+        summary.records += 1;
+        summary.errors += outcome.result.is_err() as usize;
+        match format {
+            Format::Csv => write_csv(&mut output, &outcome),
+            Format::JsonLines => write_json(&mut output, &outcome),
+        }
+    };
+    match format {
+        Format::Csv => read_csv(input, &mut emit)?,
+        Format::JsonLines => read_json_lines(input, &mut emit)?,
+    }
+    output.flush()?;
+    Ok(summary)
+}
+
+// The outcome of a record, or of a record that could not be read ("Err": the record with the
+// fields that could be read, and the error).
+fn outcome(parsed: Result<Record, (Record, String)>) -> Outcome {
+    let (record, result) = match parsed {
+        Ok(record) => {
+            let result = render(&record);
+            (record, result)
+        }
+        Err((record, error)) => (record, Err(error)),
+    };
+    Outcome {
+        line: record.line,
+        key: record.key,
+        language: record.language,
+        result,
+    }
+}
+
+fn unreadable(line: usize, error: String) -> (Record, String) {
+    (Record { line, key: String::new(), language: String::new(), args: Vec::new() }, error)
+}
+
+fn read_csv<R: BufRead>(input: R, emit: &mut impl FnMut(Outcome) -> io::Result<()>) -> io::Result<()> {
+    let mut rows = CsvRows { lines: input.lines().enumerate() };
+    let header = match rows.next() {
+        Some(row) => row?,
+        None => return Ok(()),
+    };
+    let (header_line, header) = match header {
+        (line, Ok(header)) => (line, header),
+        (line, Err(error)) => return emit(outcome(Err(unreadable(line, error)))),
+    };
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let (Some(key), Some(language)) = (column("key"), column("language")) else {
+        let error = "the header must have the columns \"key\" and \"language\"".to_string();
+        return emit(outcome(Err(unreadable(header_line, error))));
+    };
+    for row in rows {
+        let (line, fields) = row?;
+        let parsed = fields.and_then(|fields| {
+            if fields.len() != header.len() {
+                return Err(format!("{} fields, but the header has {} columns", fields.len(), header.len()));
+            }
+            let args = header
+                .iter()
+                .zip(&fields)
+                .enumerate()
+                .filter(|(index, (_, value))| *index != key && *index != language && !value.is_empty())
+                .map(|(_, (name, value))| (name.clone(), Arg::Text(value.clone())))
+                .collect();
+            Ok(Record { line, key: fields[key].clone(), language: fields[language].clone(), args })
+        });
+        emit(outcome(parsed.map_err(|error| unreadable(line, error))))?;
+    }
+    Ok(())
+}
+
+// The rows of a CSV file: the line where each row starts, and its fields. A quoted field can
+// span several lines. Blank lines are skipped.
+struct CsvRows<I> {
+    lines: I,
+}
+
+impl<I: Iterator<Item = (usize, io::Result<String>)>> Iterator for CsvRows<I> {
+    type Item = io::Result<(usize, Result<Vec<String>, String>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (index, first) = loop {
+            match self.lines.next()? {
+                (_, Ok(line)) if line.trim().is_empty() => continue,
+                (index, Ok(line)) => break (index, line),
+                (_, Err(error)) => return Some(Err(error)),
+            }
+        };
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut line = first;
+        loop {
+            let mut chars = line.trim_end_matches('\r').chars().peekable();
+            while let Some(c) = chars.next() {
+                match c {
+                    '"' if quoted && chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    }
+                    '"' if quoted => quoted = false,
+                    '"' if field.is_empty() => quoted = true,
+                    ',' if !quoted => fields.push(std::mem::take(&mut field)),
+                    c => field.push(c),
+                }
+            }
+            if !quoted {
+                break;
+            }
+            // The quoted field continues on the next line.
+            match self.lines.next() {
+                Some((_, Ok(next))) => {
+                    field.push('\n');
+                    line = next;
+                }
+                Some((_, Err(error))) => return Some(Err(error)),
+                None => return Some(Ok((index + 1, Err("unterminated quoted field".to_string())))),
+            }
+        }
+        fields.push(field);
+        Some(Ok((index + 1, Ok(fields))))
+    }
+}
+
+fn write_csv<W: Write>(out: &mut W, outcome: &Outcome) -> io::Result<()> {
+    let (text, error) = match &outcome.result {
+        Ok(text) => (text.as_str(), ""),
+        Err(error) => ("", error.as_str()),
+    };
+    write!(out, "{}", outcome.line)?;
+    for field in [outcome.key.as_str(), outcome.language.as_str(), text, error] {
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(out, ",\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(out, ",{}", field)?;
+        }
+    }
+    writeln!(out)
+}
+
+fn read_json_lines<R: BufRead>(input: R, emit: &mut impl FnMut(Outcome) -> io::Result<()>) -> io::Result<()> {
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed = match Json::parse(&line) {
+            Ok(json) => record(index + 1, json),
+            Err(error) => Err(unreadable(index + 1, error)),
+        };
+        emit(outcome(parsed))?;
+    }
+    Ok(())
+}
+
+// Read a record. When a field is wrong, the error keeps the key and the language, if they
+// could be read.
+fn record(line: usize, json: Json) -> Result<Record, (Record, String)> {
+    let Json::Object(members) = json else {
+        return Err(unreadable(line, "expected a JSON object".to_string()));
+    };
+    let mut record = Record { line, key: String::new(), language: String::new(), args: Vec::new() };
+    let (mut has_key, mut has_language) = (false, false);
+    let mut error = None;
+    for (name, value) in members {
+        let problem = match (name.as_str(), value) {
+            ("key", Json::String(key)) => {
+                (record.key, has_key) = (key, true);
+                continue;
+            }
+            ("language", Json::String(language)) => {
+                (record.language, has_language) = (language, true);
+                continue;
+            }
+            ("args", Json::Object(args)) => {
+                let mut problem = None;
+                for (name, value) in args {
+                    match value {
+                        Json::String(text) => record.args.push((name, Arg::Text(text))),
+                        Json::Number(number) => record.args.push((name, number_arg(&number))),
+                        _ => problem = Some(format!("the value of \"{}\" must be a string or a number", name)),
+                    }
+                }
+                match problem {
+                    Some(problem) => problem,
+                    None => continue,
+                }
+            }
+            ("key" | "language", _) => format!("\"{}\" must be a string", name),
+            ("args", _) => "\"args\" must be an object".to_string(),
+            (name, _) => format!("unknown field \"{}\"", name),
+        };
+        error.get_or_insert(problem);
+    }
+    let error = match (error, has_key, has_language) {
+        (None, true, true) => return Ok(record),
+        (Some(error), _, _) => error,
+        (None, false, _) => "missing field \"key\"".to_string(),
+        (None, _, false) => "missing field \"language\"".to_string(),
+    };
+    Err((record, error))
+}
+
+// A JSON number, as written: an integer, a decimal number with the same number of digits, or
+// (with an exponent or out of range) the text itself.
+fn number_arg(number: &str) -> Arg {
+    if let Ok(integer) = number.parse() {
+        return Arg::Integer(integer);
+    }
+    match (number.split_once('.'), number.parse()) {
+        (Some((_, fraction)), Ok(decimal)) if fraction.bytes().all(|byte| byte.is_ascii_digit()) => {
+            Arg::Decimal(decimal, fraction.len())
+        }
+        _ => Arg::Text(number.to_string()),
+    }
+}
+
+fn write_json<W: Write>(out: &mut W, outcome: &Outcome) -> io::Result<()> {
+    let string = |text: &str| {
+        let mut json = String::from('"');
+        for c in text.chars() {
+            match c {
+                '"' => json.push_str("\\\""),
+                '\\' => json.push_str("\\\\"),
+                '\n' => json.push_str("\\n"),
+                '\r' => json.push_str("\\r"),
+                '\t' => json.push_str("\\t"),
+                c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+                c => json.push(c),
+            }
+        }
+        json.push('"');
+        json
+    };
+    let (text, error) = match &outcome.result {
+        Ok(text) => (string(text), "null".to_string()),
+        Err(error) => ("null".to_string(), string(error)),
+    };
+    writeln!(
+        out,
+        "{{\"line\":{},\"key\":{},\"language\":{},\"text\":{},\"error\":{}}}",
+        outcome.line,
+        string(&outcome.key),
+        string(&outcome.language),
+        text,
+        error
+    )
+}
+
+// The JSON values, enough to read the records. Numbers are kept as written. The records have no
+// use for the contents of the booleans and the arrays: they are checked, then dropped.
+enum Json {
+    Null,
+    Bool,
+    Number(String),
+    String(String),
+    Array,
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser { text, position: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_spaces();
+        if parser.position < text.len() {
+            return Err(parser.error("unexpected characters after the JSON value"));
+        }
+        Ok(value)
+    }
+}
+
+// The deepest nesting of arrays and objects in a record.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    text: &'a str,
+    // Byte offset of the next character.
+    position: usize,
+    // Number of arrays and objects open at the position.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        // Columns are counted in characters, from 1.
+        format!("{} (column {})", message, self.text[..self.position].chars().count() + 1)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(c @ (' ' | '\t' | '\r' | '\n')) = self.peek() {
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_spaces();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected \"{}\"", expected)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_spaces();
+        let rest = &self.text[self.position..];
+        for (word, value) in [("null", Json::Null), ("true", Json::Bool), ("false", Json::Bool)] {
+            if rest.starts_with(word) {
+                self.position += word.len();
+                return Ok(value);
+            }
+        }
+        match self.peek() {
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(open @ ('[' | '{')) => {
+                // The values are parsed recursively: a limit keeps a hostile record from
+                // overflowing the stack.
+                if self.depth == MAX_DEPTH {
+                    return Err(self.error("arrays and objects nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if open == '[' { self.array() } else { self.object() };
+                self.depth -= 1;
+                value
+            }
+            Some('-' | '0'..='9') => {
+                let length = rest
+                    .find(|c: char| !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9'))
+                    .unwrap_or(rest.len());
+                let number = &rest[..length];
+                if !is_number(number) {
+                    return Err(self.error("invalid number"));
+                }
+                self.position += length;
+                Ok(Json::Number(number.to_string()))
+            }
+            _ => Err(self.error("expected a JSON value")),
+        }
+    }
+
+    // An array, the current character being its opening bracket.
+    fn array(&mut self) -> Result<Json, String> {
+        self.position += 1;
+        self.skip_spaces();
+        if self.peek() == Some(']') {
+            self.position += 1;
+            return Ok(Json::Array);
+        }
+        loop {
+            self.value()?;
+            self.skip_spaces();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(Json::Array);
+                }
+                _ => return Err(self.error("expected \",\" or \"]\"")),
+            }
+        }
+    }
+
+    // An object, the current character being its opening brace.
+    fn object(&mut self) -> Result<Json, String> {
+        self.position += 1;
+        let mut members = Vec::new();
+        self.skip_spaces();
+        if self.peek() == Some('}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a member name"));
+            }
+            let name = self.string()?;
+            self.expect(':')?;
+            members.push((name, self.value()?));
+            self.skip_spaces();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.error("expected \",\" or \"}\"")),
+            }
+        }
+    }
+
+    // A string, the current character being its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escape = self.peek().ok_or_else(|| self.error("unterminated string"))?;
+                    self.position += escape.len_utf8();
+                    match escape {
+                        '"' | '\\' | '/' => value.push(escape),
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => {
+                            let unit = self.hex()?;
+                            // A character outside of the BMP is written as a surrogate pair.
+                            let c = if (0xd800..0xdc00).contains(&unit) && self.text[self.position..].starts_with("\\u") {
+                                self.position += 2;
+                                let low = self.hex()?;
+                                char::from_u32(0x10000 + ((unit - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff))
+                                    .filter(|_| (0xdc00..0xe000).contains(&low))
+                            } else {
+                                char::from_u32(unit)
+                            };
+                            value.push(c.ok_or_else(|| self.error("invalid \"\\u\" escape"))?);
+                        }
+                        _ => return Err(self.error(&format!("unknown escape \"\\{}\"", escape))),
+                    }
+                }
+                c if (c as u32) < 0x20 => return Err(self.error("control character in a string")),
+                c => value.push(c),
+            }
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, String> {
+        // "from_str_radix()" also accepts a sign.
+        let digits = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        let unit = digits
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid \"\\u\" escape"))?;
+        self.position += 4;
+        Ok(unit)
+    }
+}
+
+// Whether "text" is a JSON number (RFC 8259): an optional minus sign, an integer without leading
+// zero, an optional fraction and an optional exponent, each with at least one digit.
+fn is_number(text: &str) -> bool {
+    let digits = |bytes: &[u8]| bytes.iter().take_while(|byte| byte.is_ascii_digit()).count();
+    let bytes = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    let integer = digits(bytes);
+    if integer == 0 || (integer > 1 && bytes[0] == b'0') {
+        return false;
+    }
+    let mut rest = &bytes[integer..];
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        let length = digits(fraction);
+        if length == 0 {
+            return false;
+        }
+        rest = &fraction[length..];
+    }
+    if let [b'e' | b'E', exponent @ ..] = rest {
+        let exponent = exponent.strip_prefix(b"+").or(exponent.strip_prefix(b"-")).unwrap_or(exponent);
+        let length = digits(exponent);
+        if length == 0 {
+            return false;
+        }
+        rest = &exponent[length..];
+    }
+    rest.is_empty()
+}
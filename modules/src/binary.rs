@@ -0,0 +1,372 @@
+// Compact binary form of the catalog.
+//
+// The compiler ("compile()", only with the feature "std") turns the catalog into a blob. The
+// reader ("CompiledCatalog") serves phrases straight from the blob: it does not allocate and it
+// does not copy the texts, it returns slices of the blob. The blob can be embedded into an
+// executable with "include_bytes!()" or read from a memory-mapped file.
+//
+// Phrases are indexed by a minimal perfect hash ("hash and displace"): the N identifiers
+// are mapped onto the N entries without collision. Thus, a lookup computes two hashes,
+// reads one entry and compares one identifier.
+//
+// Layout (all integers are little-endian "u32", except the format version and the flags
+// which are "u16"):
+//
+//   header       magic "MCAT", format version, flags, catalog version, number of entries,
+//                number of buckets, size of the string pool, CRC-32 of everything that
+//                follows the header
+//   buckets      one displacement per bucket
+//   entries      (identifier offset, identifier length, text offset, text length)
+//   string pool  identifiers ("<tag>\x1f<key>.<formality>") and texts (UTF-8)
+
+use core::fmt;
+
+use crate::catalog::Formality;
+#[cfg(feature = "std")]
+use crate::catalog::Language;
+
+pub const MAGIC: [u8; 4] = *b"MCAT";
+// Version 2: the language tag is hashed in lowercase, for the case-insensitive lookups.
+pub const FORMAT_VERSION: u16 = 2;
+
+const HEADER_LEN: usize = 28;
+const ENTRY_LEN: usize = 16;
+// Separates the language tag from the phrase identifier.
+const TAG_SEPARATOR: u8 = 0x1f;
+// Average number of identifiers per bucket.
+#[cfg(feature = "std")]
+const BUCKET_SIZE: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    // The blob is shorter than its header or than the sizes announced by its header.
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch { expected: u32, actual: u32 },
+    // An entry points outside of the string pool, or a text is not valid UTF-8.
+    Corrupted { entry: u32 },
+    // The catalog does not fit into the format (more than 4 GiB of strings).
+    TooLarge,
+    // A phrase is given twice: the same language is given twice, or two languages have the
+    // same tag (ignoring the case). The hash cannot separate two equal identifiers.
+    DuplicateIdentifier { tag: &'static str, key: &'static str, formality: Formality },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f, "truncated catalog"),
+            Error::BadMagic => write!(f, "not a compiled catalog (bad magic number)"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported catalog format version {}", version)
+            }
+            Error::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch (expected {:#010x}, found {:#010x})",
+                expected, actual
+            ),
+            Error::Corrupted { entry } => write!(f, "corrupted catalog entry #{}", entry),
+            Error::TooLarge => write!(f, "catalog too large"),
+            Error::DuplicateIdentifier { tag, key, formality } => write!(
+                f,
+                "duplicate phrase \"{}.{}\" in the language \"{}\"",
+                key,
+                formality.as_str(),
+                tag
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+// Turn the given languages into a blob (requires the feature "std").
+#[cfg(feature = "std")]
+pub fn compile(languages: &[&'static Language], catalog_version: u32) -> Result<Vec<u8>, Error> {
+    // Gather the identifiers and the texts.
+    let mut pool: Vec<u8> = Vec::new();
+    let mut entries: Vec<(u64, [u32; 4])> = Vec::new();
+    let mut identifiers = std::collections::HashSet::new();
+    for language in languages {
+        // Regional variants are flattened: their inherited phrases are stored too.
+        for phrase in language.entries() {
+            // The tags are case-insensitive: "en-GB" and "en-gb" are the same language.
+            let identifier = (language.tag.to_ascii_lowercase(), phrase.key, phrase.formality);
+            if !identifiers.insert(identifier) {
+                return Err(Error::DuplicateIdentifier {
+                    tag: language.tag,
+                    key: phrase.key,
+                    formality: phrase.formality,
+                });
+            }
+            let id_offset = pool.len();
+            pool.extend_from_slice(language.tag.as_bytes());
+            pool.push(TAG_SEPARATOR);
+            pool.extend_from_slice(phrase.key.as_bytes());
+            pool.push(b'.');
+            pool.extend_from_slice(phrase.formality.as_str().as_bytes());
+            let id_len = pool.len() - id_offset;
+            let text_offset = pool.len();
+            pool.extend_from_slice(phrase.text.as_bytes());
+            let seed = identifier_hash(language.tag, phrase.key, phrase.formality);
+            entries.push((
+                seed,
+                [
+                    to_u32(id_offset)?,
+                    to_u32(id_len)?,
+                    to_u32(text_offset)?,
+                    to_u32(phrase.text.len())?,
+                ],
+            ));
+        }
+    }
+
+    // Build the minimal perfect hash: the identifiers are distributed into buckets, then,
+    // starting with the largest bucket, we look for a displacement that sends all the
+    // identifiers of the bucket to free slots.
+    let count = entries.len();
+    let bucket_count = count / BUCKET_SIZE + 1;
+    let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); bucket_count];
+    for (index, (seed, _)) in entries.iter().enumerate() {
+        buckets[(*seed % bucket_count as u64) as usize].push(index);
+    }
+    let mut order: Vec<usize> = (0..bucket_count).collect();
+    order.sort_by_key(|bucket| core::cmp::Reverse(buckets[*bucket].len()));
+
+    let mut displacements: Vec<u32> = vec![0; bucket_count];
+    let mut slots: Vec<Option<usize>> = vec![None; count];
+    for bucket in order {
+        if buckets[bucket].is_empty() {
+            continue;
+        }
+        let mut displacement: u32 = 1;
+        loop {
+            let mut taken: Vec<usize> = Vec::new();
+            let fits = buckets[bucket].iter().all(|index| {
+                let slot = slot_of(entries[*index].0, displacement, count);
+                let free = slots[slot].is_none() && !taken.contains(&slot);
+                taken.push(slot);
+                free
+            });
+            if fits {
+                for (index, slot) in buckets[bucket].iter().zip(taken) {
+                    slots[slot] = Some(*index);
+                }
+                displacements[bucket] = displacement;
+                break;
+            }
+            displacement = displacement.checked_add(1).ok_or(Error::TooLarge)?;
+        }
+    }
+
+    // Serialise everything but the header, then prepend the header.
+    let mut body: Vec<u8> = Vec::with_capacity(bucket_count * 4 + count * ENTRY_LEN + pool.len());
+    for displacement in &displacements {
+        body.extend_from_slice(&displacement.to_le_bytes());
+    }
+    for slot in &slots {
+        // All the slots are taken, since the hash is minimal.
+        let (_, fields) = entries[slot.expect("every slot is assigned")];
+        for field in fields {
+            body.extend_from_slice(&field.to_le_bytes());
+        }
+    }
+    body.extend_from_slice(&pool);
+
+    let mut blob: Vec<u8> = Vec::with_capacity(HEADER_LEN + body.len());
+    blob.extend_from_slice(&MAGIC);
+    blob.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    blob.extend_from_slice(&0u16.to_le_bytes());
+    blob.extend_from_slice(&catalog_version.to_le_bytes());
+    blob.extend_from_slice(&to_u32(count)?.to_le_bytes());
+    blob.extend_from_slice(&to_u32(bucket_count)?.to_le_bytes());
+    blob.extend_from_slice(&to_u32(pool.len())?.to_le_bytes());
+    blob.extend_from_slice(&crc32(&body).to_le_bytes());
+    blob.extend_from_slice(&body);
+    Ok(blob)
+}
+
+// Zero-copy view over a blob produced by "compile()".
+#[derive(Clone, Copy, Debug)]
+pub struct CompiledCatalog<'a> {
+    catalog_version: u32,
+    count: usize,
+    buckets: &'a [u8],
+    entries: &'a [u8],
+    pool: &'a [u8],
+}
+
+impl<'a> CompiledCatalog<'a> {
+    // Check the header, the checksum and the entries. Once this is done, lookups cannot fail
+    // because of a corrupted blob.
+    pub fn from_bytes(blob: &'a [u8]) -> Result<CompiledCatalog<'a>, Error> {
+        if blob.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if blob[0..4] != MAGIC {
+            return Err(Error::BadMagic);
+        }
+        let version = u16::from_le_bytes([blob[4], blob[5]]);
+        if version != FORMAT_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        let catalog_version = read_u32(blob, 8);
+        let count = read_u32(blob, 12) as usize;
+        let bucket_count = read_u32(blob, 16) as usize;
+        let pool_len = read_u32(blob, 20) as usize;
+        let expected = read_u32(blob, 24);
+
+        let body = &blob[HEADER_LEN..];
+        let buckets_len = bucket_count.checked_mul(4).ok_or(Error::Truncated)?;
+        let entries_len = count.checked_mul(ENTRY_LEN).ok_or(Error::Truncated)?;
+        let body_len = buckets_len
+            .checked_add(entries_len)
+            .and_then(|len| len.checked_add(pool_len))
+            .ok_or(Error::Truncated)?;
+        if body.len() != body_len || (count > 0 && bucket_count == 0) {
+            return Err(Error::Truncated);
+        }
+        let actual = crc32(body);
+        if actual != expected {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+
+        let catalog = CompiledCatalog {
+            catalog_version,
+            count,
+            buckets: &body[..buckets_len],
+            entries: &body[buckets_len..buckets_len + entries_len],
+            pool: &body[buckets_len + entries_len..],
+        };
+        for index in 0..count {
+            let (id, text) = catalog.entry(index).ok_or(Error::Corrupted { entry: index as u32 })?;
+            if !id.contains(&TAG_SEPARATOR) || core::str::from_utf8(text).is_err() {
+                return Err(Error::Corrupted { entry: index as u32 });
+            }
+        }
+        Ok(catalog)
+    }
+
+    pub fn catalog_version(&self) -> u32 {
+        self.catalog_version
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    // Language tags are case-insensitive, as in "catalog::language()": "en-gb" is "en-GB".
+    pub fn lookup(&self, tag: &str, key: &str, formality: Formality) -> Option<&'a str> {
+        if self.count == 0 {
+            return None;
+        }
+        let bucket_count = self.buckets.len() / 4;
+        let seed = identifier_hash(tag, key, formality);
+        let displacement = read_u32(self.buckets, (seed % bucket_count as u64) as usize * 4);
+        let (id, text) = self.entry(slot_of(seed, displacement, self.count))?;
+
+        // The slot may hold another identifier: the hash is only perfect for the identifiers
+        // it was built from.
+        let formality = formality.as_str().as_bytes();
+        let expected_len = tag.len() + 1 + key.len() + 1 + formality.len();
+        if id.len() != expected_len {
+            return None;
+        }
+        let (id_tag, rest) = id.split_at(tag.len());
+        let (id_key, rest) = rest[1..].split_at(key.len());
+        if !id_tag.eq_ignore_ascii_case(tag.as_bytes())
+            || id[tag.len()] != TAG_SEPARATOR
+            || id_key != key.as_bytes()
+            || rest[0] != b'.'
+            || &rest[1..] != formality
+        {
+            return None;
+        }
+        core::str::from_utf8(text).ok()
+    }
+
+    // Return the identifier and the text of an entry, if they lie within the string pool.
+    fn entry(&self, index: usize) -> Option<(&'a [u8], &'a [u8])> {
+        let base = index * ENTRY_LEN;
+        let field = |n: usize| read_u32(self.entries, base + n * 4) as usize;
+        let id = self.pool.get(field(0)..field(0).checked_add(field(1))?)?;
+        let text = self.pool.get(field(2)..field(2).checked_add(field(3))?)?;
+        Some((id, text))
+    }
+}
+
+#[cfg(feature = "std")]
+fn to_u32(value: usize) -> Result<u32, Error> {
+    u32::try_from(value).map_err(|_| Error::TooLarge)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn slot_of(seed: u64, displacement: u32, count: usize) -> usize {
+    (mix(seed ^ (displacement as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)) % count as u64) as usize
+}
+
+// FNV-1a over "<tag>\x1f<key>.<formality>", computed without building the identifier. The tag is
+// hashed in lowercase.
+fn identifier_hash(tag: &str, key: &str, formality: Formality) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let parts: [&[u8]; 5] = [
+        tag.as_bytes(),
+        &[TAG_SEPARATOR],
+        key.as_bytes(),
+        b".",
+        formality.as_str().as_bytes(),
+    ];
+    for (index, part) in parts.into_iter().enumerate() {
+        for byte in part {
+            let byte = if index == 0 { byte.to_ascii_lowercase() } else { *byte };
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    mix(hash)
+}
+
+// Final avalanche step (from MurmurHash3), so that the low bits depend on all the input bits.
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+// CRC-32 (IEEE 802.3), as used by zip and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+    for byte in bytes {
+        crc = CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+static CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut crc = n as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+    table
+}
@@ -0,0 +1,243 @@
+// The catalog gathers the phrases defined by the language modules ("english", "japanese"...)
+// into a single table that can be searched by language tag, phrase key and formality.
+//
+// Everything here is "&'static": the phrases are compiled into the binary, thus looking
+// up a phrase never allocates.
+
+use core::fmt;
+
+use crate::english;
+
+// Version of the catalog contents. Bump it whenever a phrase is added, removed or modified.
+pub const VERSION: u32 = 4;
+
+// The register used by a phrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Formality {
+    Casual,
+    Polite,
+}
+
+impl Formality {
+    pub const ALL: [Formality; 2] = [Formality::Casual, Formality::Polite];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Formality::Casual => "casual",
+            Formality::Polite => "polite",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Formality> {
+        match text {
+            "casual" => Some(Formality::Casual),
+            "polite" => Some(Formality::Polite),
+            _ => None,
+        }
+    }
+}
+
+// A phrase is identified by its key ("greeting", "farewell"...) and its formality.
+// The textual form of the identifier is "key.formality" (for example: "farewell.polite").
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Phrase {
+    pub key: &'static str,
+    // The same key, checked at compile time ("Key::Greeting" for "greeting").
+    pub checked_key: Key,
+    pub formality: Formality,
+    pub text: &'static str,
+    // Japanese only: the text annotated with the readings of its kanji, as "[base|reading]"
+    // (see "furigana.rs"). "None" if the text has no kanji.
+    pub ruby: Option<&'static str>,
+    // Review metadata of a translation ("None" for the source language, see "review.rs").
+    pub review: Option<crate::review::Review>,
+}
+
+#[derive(Debug)]
+pub struct Language {
+    // BCP 47 language tag ("en", "ja", "en-GB"...).
+    pub tag: &'static str,
+    // English name of the language.
+    pub name: &'static str,
+    // Position of the language in "LANGUAGES".
+    pub index: usize,
+    // For a regional variant ("en-GB"), the base language ("en"). The variant only defines the
+    // phrases that differ from its base language, it inherits the other ones.
+    pub parent: Option<&'static Language>,
+    pub phrases: &'static [Phrase],
+}
+
+impl Language {
+    // The phrase defined by the language, or inherited from its base language. "None" if the
+    // language lacks the translation (see "missing()").
+    pub fn phrase(&self, key: &str, formality: Formality) -> Option<&'static Phrase> {
+        self.phrases
+            .iter()
+            .find(|phrase| phrase.key == key && phrase.formality == formality)
+            .or_else(|| self.parent?.phrase(key, formality))
+    }
+
+    // Same as "phrase()", with a key checked at compile time ("Key::Farewell"). A missing
+    // translation gives the phrase of the source language, which defines every phrase
+    // ("build.rs" checks it): this cannot fail.
+    pub fn get(&self, key: Key, formality: Formality) -> &'static Phrase {
+        self.phrase(key.as_str(), formality)
+            .or_else(|| LANGUAGES[0].phrase(key.as_str(), formality))
+            .expect("build.rs checks that the source language defines every phrase")
+    }
+
+    // All the phrases of the language, including the inherited ones.
+    pub fn entries(&'static self) -> Entries {
+        Entries {
+            language: self,
+            level: Some(self),
+            index: 0,
+        }
+    }
+}
+
+// Iterator returned by "Language::entries()".
+pub struct Entries {
+    language: &'static Language,
+    // The language whose phrases are being visited: the language itself, then its parent...
+    level: Option<&'static Language>,
+    index: usize,
+}
+
+impl Iterator for Entries {
+    type Item = &'static Phrase;
+
+    fn next(&mut self) -> Option<&'static Phrase> {
+        loop {
+            let level = self.level?;
+            match level.phrases.get(self.index) {
+                None => {
+                    self.level = level.parent;
+                    self.index = 0;
+                }
+                Some(phrase) => {
+                    self.index += 1;
+                    // Skip the phrases of the parent that the language overrides.
+                    let visible = self.language.phrase(phrase.key, phrase.formality);
+                    if visible.is_some_and(|visible| core::ptr::eq(visible, phrase)) {
+                        return Some(phrase);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// "LANGUAGES", "LANGUAGE_COUNT", "KEYS" and "Key" are generated by "build.rs" from "phrases.txt".
+include!(concat!(env!("OUT_DIR"), "/languages.rs"));
+
+// Split a textual phrase identifier ("farewell.polite") into its key and its formality.
+pub fn parse_id(id: &str) -> Option<(&str, Formality)> {
+    let (key, formality) = id.rsplit_once('.')?;
+    Some((key, Formality::parse(formality)?))
+}
+
+// Language tags are case-insensitive: "en-gb" is "en-GB".
+pub fn language(tag: &str) -> Option<&'static Language> {
+    LANGUAGES
+        .iter()
+        .copied()
+        .find(|language| language.tag.eq_ignore_ascii_case(tag))
+}
+
+// Find the language that serves a tag: the language itself if it is compiled into the crate,
+// otherwise the closest parent ("en-GB-scotland" is served by "en-GB", "en-AU" by "en"),
+// otherwise English.
+pub fn resolve(tag: &str) -> &'static Language {
+    let mut tag = tag;
+    loop {
+        if let Some(language) = language(tag) {
+            return language;
+        }
+        match tag.rsplit_once('-') {
+            Some((parent, _)) => tag = parent,
+            None => return &english::LANGUAGE,
+        }
+    }
+}
+
+// The text of a phrase. If the language lacks the translation, the text of the source language
+// is returned.
+pub fn lookup(tag: &str, key: &str, formality: Formality) -> Option<&'static str> {
+    let language = language(tag)?;
+    let phrase = language
+        .phrase(key, formality)
+        .or_else(|| LANGUAGES[0].phrase(key, formality))?;
+    Some(served(language, phrase))
+}
+
+// Completeness check: the phrases of the source language (the first language) that "language"
+// does not define, nor inherit.
+pub fn missing(language: &'static Language) -> impl Iterator<Item = &'static Phrase> {
+    LANGUAGES[0]
+        .phrases
+        .iter()
+        .filter(move |phrase| language.phrase(phrase.key, phrase.formality).is_none())
+}
+
+// Called by the lookups when "phrase" is served for a lookup in "language". When the feature
+// "metrics" is disabled, this does nothing and the compiler removes it.
+#[inline(always)]
+pub(crate) fn served(language: &'static Language, phrase: &'static Phrase) -> &'static str {
+    #[cfg(feature = "metrics")]
+    crate::metrics::record(language, phrase);
+    #[cfg(not(feature = "metrics"))]
+    let _ = language;
+    phrase.text
+}
+
+// Write a phrase, replacing its placeholders ("{name}") by the given values. "{{" and "}}" stand
+// for "{" and "}". Placeholders without value are written unchanged.
+pub fn render<W: fmt::Write>(out: &mut W, text: &str, args: &[(&str, &dyn fmt::Display)]) -> fmt::Result {
+    let mut rest = text;
+    while let Some(start) = rest.find(['{', '}']) {
+        out.write_str(&rest[..start])?;
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.write_str(&tail[..1])?;
+            rest = &tail[2..];
+            continue;
+        }
+        match (tail.starts_with('{'), tail.find('}')) {
+            (true, Some(end)) => {
+                let name = &tail[1..end];
+                match args.iter().find(|(arg, _)| *arg == name) {
+                    Some((_, value)) => write!(out, "{}", value)?,
+                    None => out.write_str(&tail[..=end])?,
+                }
+                rest = &tail[end + 1..];
+            }
+            _ => {
+                out.write_str(&tail[..1])?;
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.write_str(rest)
+}
+
+// The names of the placeholders of a phrase, in order ("{name}" gives "name"), following the
+// rules of "render()".
+pub fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    core::iter::from_fn(move || loop {
+        let start = rest.find(['{', '}'])?;
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            rest = &tail[2..];
+            continue;
+        }
+        match (tail.starts_with('{'), tail.find('}')) {
+            (true, Some(end)) => {
+                rest = &tail[end + 1..];
+                return Some(&tail[1..end]);
+            }
+            _ => rest = &tail[1..],
+        }
+    })
+}
@@ -0,0 +1,317 @@
+// Locale-aware collation: the order in which a user of a locale expects to see a list.
+//
+// The comparison works like the Unicode Collation Algorithm, with four levels. Each character
+// is turned into collation elements (primary, secondary, tertiary weights):
+//
+// - level 1 (primary): the base letters. "a", "A" and "à" are equal; punctuation and spaces are
+//   ignored ("{name}" sorts as "name").
+// - level 2 (secondary): the diacritics ("a" < "à"), the voicing marks of the kana ("か" < "が").
+// - level 3 (tertiary): the case ("a" < "A"), small kana ("ゃ" < "や"), hiragana before katakana.
+// - level 4: the code points, so that only identical strings are equal.
+//
+// The strings are compared level by level: a difference at level 2 only counts if the strings
+// are equal at level 1. Thus "résumé" sorts between "resume" and "Resumes".
+//
+// Primary order: digits, Latin letters, other alphabets (Greek, Cyrillic...), kana in gojūon
+// order (あいうえお かきくけこ...), Hangul, then CJK ideographs (in code point order). Tailorings:
+// Spanish sorts "ñ" as a letter of its own, after "n"; Japanese sorts the prolonged sound mark
+// "ー" as the vowel it prolongs ("カー" sorts as "かあ").
+//
+// Limits: the CJK ideographs have no tailoring, they fall through to the code point order. A
+// list in simplified Chinese is thus not sorted by pinyin or by stroke count ("zh-Hans" uses
+// the default order), and Japanese kanji are not sorted by reading ("漢字" does not sort as
+// "かんじ"): only the kana are in gojūon order.
+
+use core::cmp::Ordering;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Collator {
+    spanish: bool,
+    japanese: bool,
+}
+
+impl Collator {
+    // The collator of a locale ("ja", "es-MX"...). Unknown locales use the default order.
+    pub fn new(tag: &str) -> Collator {
+        let language = tag.split('-').next().unwrap_or("");
+        Collator {
+            spanish: language.eq_ignore_ascii_case("es"),
+            japanese: language.eq_ignore_ascii_case("ja"),
+        }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        for level in 1..=3 {
+            let ordering = self.weights(a, level).cmp(self.weights(b, level));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        a.cmp(b)
+    }
+
+    // Sort a list of items by the text returned by "key".
+    #[cfg(feature = "std")]
+    pub fn sort_by_key<T>(&self, items: &mut [T], key: impl Fn(&T) -> &str) {
+        items.sort_by(|a, b| self.compare(key(a), key(b)));
+    }
+
+    // The weights of a text at one level. The ignorable characters (primary weight 0) are
+    // skipped at the first three levels.
+    fn weights<'a>(&self, text: &'a str, level: usize) -> impl Iterator<Item = u32> + 'a {
+        self.elements(text)
+            .filter(|element| element.primary != 0)
+            .map(move |element| match level {
+                1 => element.primary,
+                2 => element.secondary as u32,
+                _ => element.tertiary as u32,
+            })
+    }
+
+    fn elements<'a>(&self, text: &'a str) -> Elements<'a> {
+        Elements {
+            collator: *self,
+            chars: text.chars(),
+            pending: None,
+            vowel: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Element {
+    primary: u32,
+    secondary: u8,
+    tertiary: u8,
+}
+
+const DIGITS: u32 = 0x100;
+const LATIN: u32 = 0x200;
+const OTHER_ALPHABETS: u32 = 0x1000;
+const KANA: u32 = 0x11_0000;
+const HANGUL: u32 = 0x12_0000;
+const IDEOGRAPHS: u32 = 0x20_0000;
+const OTHERS: u32 = 0x30_0000;
+
+// Tertiary weights.
+const LOWER: u8 = 0;
+const UPPER: u8 = 1;
+const WIDE: u8 = 2;
+
+struct Elements<'a> {
+    collator: Collator,
+    chars: core::str::Chars<'a>,
+    // Second element of an expansion ("ß" -> "ss").
+    pending: Option<Element>,
+    // Japanese: the vowel (0 to 4, "あいうえお") of the last kana, for "ー".
+    vowel: Option<u32>,
+}
+
+impl Iterator for Elements<'_> {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Element> {
+        if let Some(element) = self.pending.take() {
+            return Some(element);
+        }
+        let c = self.chars.next()?;
+        let (element, expansion) = self.element(c);
+        self.pending = expansion;
+        Some(element)
+    }
+}
+
+impl Elements<'_> {
+    fn element(&mut self, c: char) -> (Element, Option<Element>) {
+        let element = |primary: u32, secondary: u8, tertiary: u8| Element { primary, secondary, tertiary };
+
+        // Kana.
+        if let Some((index, voicing, small, katakana)) = kana(c) {
+            self.vowel = vowel(index);
+            let tertiary = (small as u8 ^ 1) | (katakana as u8) << 1;
+            return (element(KANA + index, voicing, tertiary), None);
+        }
+        if c == 'ー' && self.collator.japanese {
+            if let Some(vowel) = self.vowel {
+                return (element(KANA + vowel, 0, 4), None);
+            }
+        }
+        self.vowel = None;
+
+        // Fullwidth ASCII letters and digits sort as ASCII, after them.
+        let (c, width) = match c {
+            '\u{ff10}'..='\u{ff19}' | '\u{ff21}'..='\u{ff3a}' | '\u{ff41}'..='\u{ff5a}' => {
+                (char::from_u32(c as u32 - 0xfee0).unwrap_or(c), WIDE)
+            }
+            c => (c, 0),
+        };
+        let case = if c.is_uppercase() { UPPER } else { LOWER };
+        let lower = c.to_lowercase().next().unwrap_or(c);
+
+        if let Some(digit) = lower.to_digit(10) {
+            return (element(DIGITS + digit, 0, width), None);
+        }
+        if let Some((base, accent)) = latin(lower) {
+            let primary = |letter: char| LATIN + (letter as u32 - 'a' as u32) * 2;
+            if self.collator.spanish && lower == 'ñ' {
+                return (element(primary('n') + 1, 0, case | width), None);
+            }
+            return (element(primary(base), accent, case | width), None);
+        }
+        // Ligatures expand into two letters.
+        if let Some((first, second)) = match lower {
+            'ß' => Some(('s', 's')),
+            'æ' => Some(('a', 'e')),
+            'œ' => Some(('o', 'e')),
+            _ => None,
+        } {
+            let primary = |letter: char| LATIN + (letter as u32 - 'a' as u32) * 2;
+            return (
+                element(primary(first), 0, case),
+                Some(element(primary(second), 0, case)),
+            );
+        }
+        match c {
+            '\u{ac00}'..='\u{d7a3}' | '\u{1100}'..='\u{11ff}' | '\u{3130}'..='\u{318f}' => {
+                (element(HANGUL + c as u32, 0, 0), None)
+            }
+            '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{f900}'..='\u{faff}' => {
+                (element(IDEOGRAPHS + c as u32, 0, 0), None)
+            }
+            c if c.is_alphabetic() => (element(OTHER_ALPHABETS + lower as u32, 0, case), None),
+            c if c.is_alphanumeric() => (element(OTHERS + c as u32, 0, 0), None),
+            // Spaces, punctuation and symbols are ignorable.
+            _ => (element(0, 0, 0), None),
+        }
+    }
+}
+
+// Base letter and accent of a lowercase Latin letter.
+fn latin(c: char) -> Option<(char, u8)> {
+    const GRAVE: u8 = 1;
+    const ACUTE: u8 = 2;
+    const CIRCUMFLEX: u8 = 3;
+    const TILDE: u8 = 4;
+    const DIAERESIS: u8 = 5;
+    const RING: u8 = 6;
+    const CEDILLA: u8 = 7;
+    const CARON: u8 = 8;
+    const MACRON: u8 = 9;
+    const STROKE: u8 = 10;
+    const DOUBLE_ACUTE: u8 = 11;
+    const OGONEK: u8 = 12;
+    const DOT: u8 = 13;
+    const BREVE: u8 = 14;
+    let found = match c {
+        'a'..='z' => (c, 0),
+        'à' => ('a', GRAVE),
+        'á' => ('a', ACUTE),
+        'â' => ('a', CIRCUMFLEX),
+        'ã' => ('a', TILDE),
+        'ä' => ('a', DIAERESIS),
+        'å' => ('a', RING),
+        'ā' => ('a', MACRON),
+        'ă' => ('a', BREVE),
+        'ą' => ('a', OGONEK),
+        'ç' => ('c', CEDILLA),
+        'ć' => ('c', ACUTE),
+        'č' => ('c', CARON),
+        'ď' => ('d', CARON),
+        'đ' => ('d', STROKE),
+        'è' => ('e', GRAVE),
+        'é' => ('e', ACUTE),
+        'ê' => ('e', CIRCUMFLEX),
+        'ë' => ('e', DIAERESIS),
+        'ē' => ('e', MACRON),
+        'ė' => ('e', DOT),
+        'ę' => ('e', OGONEK),
+        'ě' => ('e', CARON),
+        'ğ' => ('g', BREVE),
+        'ì' => ('i', GRAVE),
+        'í' => ('i', ACUTE),
+        'î' => ('i', CIRCUMFLEX),
+        'ï' => ('i', DIAERESIS),
+        'ī' => ('i', MACRON),
+        'į' => ('i', OGONEK),
+        'ł' => ('l', STROKE),
+        'ñ' => ('n', TILDE),
+        'ń' => ('n', ACUTE),
+        'ň' => ('n', CARON),
+        'ò' => ('o', GRAVE),
+        'ó' => ('o', ACUTE),
+        'ô' => ('o', CIRCUMFLEX),
+        'õ' => ('o', TILDE),
+        'ö' => ('o', DIAERESIS),
+        'ø' => ('o', STROKE),
+        'ō' => ('o', MACRON),
+        'ő' => ('o', DOUBLE_ACUTE),
+        'ř' => ('r', CARON),
+        'ś' => ('s', ACUTE),
+        'š' => ('s', CARON),
+        'ş' => ('s', CEDILLA),
+        'ť' => ('t', CARON),
+        'ù' => ('u', GRAVE),
+        'ú' => ('u', ACUTE),
+        'û' => ('u', CIRCUMFLEX),
+        'ü' => ('u', DIAERESIS),
+        'ū' => ('u', MACRON),
+        'ů' => ('u', RING),
+        'ű' => ('u', DOUBLE_ACUTE),
+        'ų' => ('u', OGONEK),
+        'ý' => ('y', ACUTE),
+        'ÿ' => ('y', DIAERESIS),
+        'ź' => ('z', ACUTE),
+        'ż' => ('z', DOT),
+        'ž' => ('z', CARON),
+        _ => return None,
+    };
+    Some(found)
+}
+
+// Hiragana from U+3041 to U+3096: (gojūon index << 3) | (voicing << 1) | small. The gojūon
+// index is the position of the base kana in "あいうえおかきくけこ...わゐゑをん", the voicing is 1
+// for the dakuten ("が") and 2 for the handakuten ("ぱ").
+static HIRAGANA: [u16; 86] = [
+    1, 0, 9, 8, 17, 16, 25, 24, 33, 32, 40, 42, 48, 50, 56, 58, //
+    64, 66, 72, 74, 80, 82, 88, 90, 96, 98, 104, 106, 112, 114, 120, 122, //
+    128, 130, 137, 136, 138, 144, 146, 152, 154, 160, 168, 176, 184, 192, 200, 202, //
+    204, 208, 210, 212, 216, 218, 220, 224, 226, 228, 232, 234, 236, 240, 248, 256, //
+    264, 272, 281, 280, 289, 288, 297, 296, 304, 312, 320, 328, 336, 345, 344, 352, //
+    360, 368, 376, 18, 41, 65,
+];
+
+// Return the gojūon index, the voicing, "small" and "katakana" of a kana.
+fn kana(c: char) -> Option<(u32, u8, bool, bool)> {
+    let (hiragana, katakana) = match c {
+        '\u{3041}'..='\u{3096}' => (c as u32, false),
+        '\u{30a1}'..='\u{30f6}' => (c as u32 - 0x60, true),
+        // "ヷヸヹヺ": voiced "ワヰヱヲ".
+        '\u{30f7}'..='\u{30fa}' => {
+            let index = [43, 44, 45, 46][(c as u32 - 0x30f7) as usize];
+            return Some((index, 1, false, true));
+        }
+        _ => return None,
+    };
+    let packed = HIRAGANA[(hiragana - 0x3041) as usize];
+    Some(((packed >> 3) as u32, ((packed >> 1) & 3) as u8, packed & 1 == 1, katakana))
+}
+
+// The vowel ("あいうえお", 0 to 4) of a kana, given its gojūon index.
+fn vowel(index: u32) -> Option<u32> {
+    match index {
+        0..=34 => Some(index % 5),
+        // "やゆよ"
+        35 => Some(0),
+        36 => Some(2),
+        37 => Some(4),
+        // "らりるれろ"
+        38..=42 => Some(index - 38),
+        // "わゐゑを"
+        43 => Some(0),
+        44 => Some(1),
+        45 => Some(3),
+        46 => Some(4),
+        // "ん"
+        _ => None,
+    }
+}
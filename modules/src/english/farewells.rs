@@ -1,3 +0,0 @@
-pub fn say() {
-    println!("Farewell in English!")
-}
\ No newline at end of file
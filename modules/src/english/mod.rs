@@ -1,19 +1,27 @@
-// We **CREATE** two sub-modules, with the keyword "mod".
-
-// This tells Cargo to look for the files "src/english/farewells.rs" or "src/english/farewells/mod.rs".
-// The "pub" keyword makes the sub module "english::farewell" public.
-pub mod farewells;
-
-// This tells Cargo to look for the files "src/english/greetings.rs" or "src/english/greetings/mod.rs".
-// The "pub" keyword makes the sub module "english::greetings" public.
-pub mod greetings;
+// The sub-modules "farewells", "greetings" and "welcome" are **GENERATED** by "build.rs" from the
+// file "phrases.txt". The generated code is written into the directory "$OUT_DIR" (which is set
+// by Cargo), then it is inserted here by "include!()", as if it had been written in this file.
+//
+// The generated file contains, for example:
+//
+//     pub mod greetings { ... }
+//     pub mod farewells { ... }
+//     pub static LANGUAGE: crate::catalog::Language = ...;
+//     pub mod gb { ... }      // Regional variant "en-GB"
+//     pub mod us { ... }      // Regional variant "en-US"
+include!(concat!(env!("OUT_DIR"), "/english.rs"));
 
 // This tells Cargo to look for the files "src/english/slang.rs" or "src/english/slang/mod.rs".
 // Please note that the keyword "mod" is not preceded by the keyword "pub".
 // Therefore, the sub-module "slang" will not be accessible from the outside of the "english"
 // module.
+//
+// "#[cfg(feature = "std")]" means that the item that follows only exists when the feature "std"
+// is enabled (see "Cargo.toml"): the slang module prints, thus it needs the standard library.
+#[cfg(feature = "std")]
 mod slang;
 
+#[cfg(feature = "std")]
 pub fn tell_slang() {
     slang::tell_slang();
 }
@@ -1,4 +1,6 @@
 
+// Nothing calls this function: the module "wtf" is private (see "slang.rs").
+#[allow(dead_code)]
 pub fn wtf() {
     println!("WTF");
 }
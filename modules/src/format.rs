@@ -0,0 +1,429 @@
+// Dates, times and numbers written the way the readers of a language expect.
+//
+// "Conventions::new(tag)" gives the conventions of a language. Its methods return values that
+// implement "Display", thus they can be passed as placeholder values to "catalog::render()":
+//
+//     let ja = Conventions::new("ja");
+//     catalog::render(&mut out, text, &[("date", &ja.date(date)), ("count", &ja.number(1234))])
+//
+// For example, the date 2022-03-21 is written:
+//
+// - "Monday 21 March 2022" in British English ("en", "en-GB"),
+// - "Monday, March 21, 2022" in American English ("en-US"),
+// - "2022年3月21日月曜日" in Japanese, or "令和4年3月21日月曜日" with the era names,
+// - "lundi 21 mars 2022", "Montag, 21. März 2022", "lunes, 21 de marzo de 2022",
+//   "2022년 3월 21일 월요일", "2022年3月21日星期一".
+//
+// The numbers are grouped by thousands ("1,234,567.5" in English, "1.234.567,5" in German) and
+// the ordinals follow the language ("21st", "21e", "21.", "21.º", "21番目").
+
+use core::fmt;
+
+use crate::catalog::Language;
+
+// A day of the (proleptic) Gregorian calendar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    // "None" if the date does not exist (2022-02-29).
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Date> {
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    // The date of a number of days since 1970-01-01 (negative before).
+    pub fn from_unix_days(days: i64) -> Date {
+        // See "civil_from_days" in http://howardhinnant.github.io/date_algorithms.html.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u8;
+        let year = year_of_era + era * 400 + (month <= 2) as i64;
+        Date { year: year as i32, month, day }
+    }
+
+    // Today, in UTC.
+    #[cfg(feature = "std")]
+    pub fn today() -> Date {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+        Date::from_unix_days(seconds.div_euclid(86_400))
+    }
+
+    pub fn year(self) -> i32 {
+        self.year
+    }
+
+    pub fn month(self) -> u8 {
+        self.month
+    }
+
+    pub fn day(self) -> u8 {
+        self.day
+    }
+
+    // Number of days since 1970-01-01.
+    pub fn unix_days(self) -> i64 {
+        let year = self.year as i64 - (self.month <= 2) as i64;
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = self.month as i64;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+
+    // 0 for Sunday, 1 for Monday... 6 for Saturday.
+    pub fn weekday(self) -> u8 {
+        // 1970-01-01 was a Thursday.
+        (self.unix_days() + 4).rem_euclid(7) as u8
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// A time of the day, to the minute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Time {
+    hour: u8,
+    minute: u8,
+}
+
+impl Time {
+    // "None" if the time does not exist (24:00).
+    pub fn new(hour: u8, minute: u8) -> Option<Time> {
+        if hour > 23 || minute > 59 {
+            return None;
+        }
+        Some(Time { hour, minute })
+    }
+
+    pub fn hour(self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(self) -> u8 {
+        self.minute
+    }
+}
+
+// The languages whose conventions are known. The other languages use the British English ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Style {
+    British,
+    American,
+    Japanese,
+    French,
+    German,
+    Spanish,
+    Korean,
+    Chinese,
+}
+
+// The formatting conventions of a language.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conventions {
+    style: Style,
+}
+
+impl Conventions {
+    // The conventions of a language tag ("ja", "en-US"...). Tags are case-insensitive.
+    pub fn new(tag: &str) -> Conventions {
+        let mut subtags = tag.split('-');
+        let language = subtags.next().unwrap_or("");
+        let is = |name: &str| language.eq_ignore_ascii_case(name);
+        let style = if is("en") && subtags.any(|subtag| subtag.eq_ignore_ascii_case("us")) {
+            Style::American
+        } else if is("ja") {
+            Style::Japanese
+        } else if is("fr") {
+            Style::French
+        } else if is("de") {
+            Style::German
+        } else if is("es") {
+            Style::Spanish
+        } else if is("ko") {
+            Style::Korean
+        } else if is("zh") {
+            Style::Chinese
+        } else {
+            Style::British
+        };
+        Conventions { style }
+    }
+
+    pub fn of(language: &Language) -> Conventions {
+        Conventions::new(language.tag)
+    }
+
+    // The date in full, with the day of the week: "Monday 21 March 2022".
+    pub fn date(self, date: Date) -> Formatted {
+        self.formatted(Value::Date(date))
+    }
+
+    // The date in digits: "21/03/2022".
+    pub fn short_date(self, date: Date) -> Formatted {
+        self.formatted(Value::ShortDate(date))
+    }
+
+    // Japanese only: the date in full with the era name instead of the year: "令和4年3月21日月曜日".
+    // The other languages, and the dates before the Meiji era, use "date()".
+    pub fn era_date(self, date: Date) -> Formatted {
+        self.formatted(Value::EraDate(date))
+    }
+
+    // "15:05", or "3:05 PM" in American English.
+    pub fn time(self, time: Time) -> Formatted {
+        self.formatted(Value::Time(time))
+    }
+
+    // An integer, grouped by thousands: "1,234,567".
+    pub fn number(self, number: i64) -> Formatted {
+        self.formatted(Value::Number(number))
+    }
+
+    // A decimal number with "digits" digits after the decimal separator: "1,234.50".
+    pub fn decimal(self, number: f64, digits: usize) -> Formatted {
+        self.formatted(Value::Decimal(number, digits))
+    }
+
+    // An ordinal number: "21st" in English, "21e" in French, "21番目" in Japanese.
+    pub fn ordinal(self, number: u64) -> Formatted {
+        self.formatted(Value::Ordinal(number))
+    }
+
+    fn formatted(self, value: Value) -> Formatted {
+        Formatted { conventions: self, value }
+    }
+
+    // Thousands separator, decimal separator, and minimum number of digits for grouping.
+    fn separators(self) -> (&'static str, char, usize) {
+        match self.style {
+            // Narrow no-break space.
+            Style::French => ("\u{202f}", ',', 4),
+            Style::German => (".", ',', 4),
+            // "1234" but "12.345".
+            Style::Spanish => (".", ',', 5),
+            _ => (",", '.', 4),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Value {
+    Date(Date),
+    ShortDate(Date),
+    EraDate(Date),
+    Time(Time),
+    Number(i64),
+    Decimal(f64, usize),
+    Ordinal(u64),
+}
+
+// A value formatted with the conventions of a language, returned by the methods of
+// "Conventions".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Formatted {
+    conventions: Conventions,
+    value: Value,
+}
+
+impl fmt::Display for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let conventions = self.conventions;
+        match self.value {
+            Value::Date(date) => write_date(f, conventions.style, date),
+            Value::ShortDate(date) => write_short_date(f, conventions.style, date),
+            Value::EraDate(date) => match (conventions.style, era(date)) {
+                (Style::Japanese, Some((name, year))) => {
+                    match year {
+                        1 => write!(f, "{}元年", name)?,
+                        year => write!(f, "{}{}年", name, year)?,
+                    }
+                    write!(f, "{}月{}日{}", date.month, date.day, JAPANESE_WEEKDAYS[date.weekday() as usize])
+                }
+                (style, _) => write_date(f, style, date),
+            },
+            Value::Time(time) => write_time(f, conventions.style, time),
+            Value::Number(number) => write_grouped(f, conventions, format_args!("{}", number)),
+            Value::Decimal(number, digits) => write_grouped(f, conventions, format_args!("{:.*}", digits, number)),
+            Value::Ordinal(number) => {
+                let (prefix, suffix) = match conventions.style {
+                    Style::British | Style::American => match (number % 10, number % 100) {
+                        (_, 11..=13) => ("", "th"),
+                        (1, _) => ("", "st"),
+                        (2, _) => ("", "nd"),
+                        (3, _) => ("", "rd"),
+                        _ => ("", "th"),
+                    },
+                    Style::Japanese => ("", "番目"),
+                    Style::French => ("", if number == 1 { "er" } else { "e" }),
+                    Style::German => ("", "."),
+                    Style::Spanish => ("", ".º"),
+                    Style::Korean => ("", "번째"),
+                    Style::Chinese => ("第", ""),
+                };
+                f.write_str(prefix)?;
+                write_grouped(f, conventions, format_args!("{}", number))?;
+                f.write_str(suffix)
+            }
+        }
+    }
+}
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+const ENGLISH_WEEKDAYS: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+const JAPANESE_WEEKDAYS: [&str; 7] = ["日曜日", "月曜日", "火曜日", "水曜日", "木曜日", "金曜日", "土曜日"];
+const FRENCH_MONTHS: [&str; 12] = [
+    "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août", "septembre", "octobre", "novembre",
+    "décembre",
+];
+const FRENCH_WEEKDAYS: [&str; 7] = ["dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi"];
+const GERMAN_MONTHS: [&str; 12] = [
+    "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August", "September", "Oktober", "November", "Dezember",
+];
+const GERMAN_WEEKDAYS: [&str; 7] = ["Sonntag", "Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag"];
+const SPANISH_MONTHS: [&str; 12] = [
+    "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto", "septiembre", "octubre", "noviembre",
+    "diciembre",
+];
+const SPANISH_WEEKDAYS: [&str; 7] = ["domingo", "lunes", "martes", "miércoles", "jueves", "viernes", "sábado"];
+const KOREAN_WEEKDAYS: [&str; 7] = ["일요일", "월요일", "화요일", "수요일", "목요일", "금요일", "토요일"];
+const CHINESE_WEEKDAYS: [&str; 7] = ["星期日", "星期一", "星期二", "星期三", "星期四", "星期五", "星期六"];
+
+fn write_date(f: &mut fmt::Formatter<'_>, style: Style, date: Date) -> fmt::Result {
+    let (year, month, day) = (date.year, date.month as usize - 1, date.day);
+    let weekday = date.weekday() as usize;
+    match style {
+        Style::British => write!(f, "{} {} {} {}", ENGLISH_WEEKDAYS[weekday], day, ENGLISH_MONTHS[month], year),
+        Style::American => write!(f, "{}, {} {}, {}", ENGLISH_WEEKDAYS[weekday], ENGLISH_MONTHS[month], day, year),
+        Style::Japanese => write!(f, "{}年{}月{}日{}", year, month + 1, day, JAPANESE_WEEKDAYS[weekday]),
+        Style::French => write!(f, "{} {} {} {}", FRENCH_WEEKDAYS[weekday], day, FRENCH_MONTHS[month], year),
+        Style::German => write!(f, "{}, {}. {} {}", GERMAN_WEEKDAYS[weekday], day, GERMAN_MONTHS[month], year),
+        Style::Spanish => write!(f, "{}, {} de {} de {}", SPANISH_WEEKDAYS[weekday], day, SPANISH_MONTHS[month], year),
+        Style::Korean => write!(f, "{}년 {}월 {}일 {}", year, month + 1, day, KOREAN_WEEKDAYS[weekday]),
+        Style::Chinese => write!(f, "{}年{}月{}日{}", year, month + 1, day, CHINESE_WEEKDAYS[weekday]),
+    }
+}
+
+fn write_short_date(f: &mut fmt::Formatter<'_>, style: Style, date: Date) -> fmt::Result {
+    let Date { year, month, day } = date;
+    match style {
+        Style::British | Style::French => write!(f, "{:02}/{:02}/{}", day, month, year),
+        Style::American => write!(f, "{}/{}/{}", month, day, year),
+        Style::Japanese => write!(f, "{}/{:02}/{:02}", year, month, day),
+        Style::German => write!(f, "{:02}.{:02}.{}", day, month, year),
+        Style::Spanish => write!(f, "{}/{}/{}", day, month, year),
+        Style::Korean => write!(f, "{}. {}. {}.", year, month, day),
+        Style::Chinese => write!(f, "{}/{}/{}", year, month, day),
+    }
+}
+
+fn write_time(f: &mut fmt::Formatter<'_>, style: Style, time: Time) -> fmt::Result {
+    let Time { hour, minute } = time;
+    // The 12-hour clock: 0:30 is "12:30 AM".
+    let twelve = if hour % 12 == 0 { 12 } else { hour % 12 };
+    match style {
+        Style::American => write!(f, "{}:{:02} {}", twelve, minute, if hour < 12 { "AM" } else { "PM" }),
+        Style::Korean => write!(f, "{} {}:{:02}", if hour < 12 { "오전" } else { "오후" }, twelve, minute),
+        _ => write!(f, "{}:{:02}", hour, minute),
+    }
+}
+
+// The Japanese eras since the adoption of the Gregorian calendar: name and first day.
+const ERAS: [(&str, i32, u8, u8); 5] = [
+    ("令和", 2019, 5, 1),
+    ("平成", 1989, 1, 8),
+    ("昭和", 1926, 12, 25),
+    ("大正", 1912, 7, 30),
+    ("明治", 1868, 10, 23),
+];
+
+// The era of a date and the year in this era (1 for the first year).
+fn era(date: Date) -> Option<(&'static str, i32)> {
+    ERAS.iter()
+        .find(|(_, year, month, day)| (date.year, date.month, date.day) >= (*year, *month, *day))
+        .map(|(name, year, _, _)| (*name, date.year - year + 1))
+}
+
+// Write a number formatted by "core" ("-1234567.50"), with the separators of the language.
+// The number is formatted twice: once to count the digits of its integer part, then to write it.
+fn write_grouped(f: &mut fmt::Formatter<'_>, conventions: Conventions, number: fmt::Arguments<'_>) -> fmt::Result {
+    struct Count(usize, bool);
+    impl fmt::Write for Count {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            for c in text.chars() {
+                match c {
+                    '.' => self.1 = true,
+                    '0'..='9' if !self.1 => self.0 += 1,
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct Group<'a, 'b> {
+        out: &'a mut fmt::Formatter<'b>,
+        separators: (&'static str, char, usize),
+        // Digits of the integer part that remain to be written.
+        remaining: usize,
+        grouped: bool,
+    }
+    impl fmt::Write for Group<'_, '_> {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            let (thousands, decimal, _) = self.separators;
+            for c in text.chars() {
+                match c {
+                    '.' => {
+                        self.remaining = 0;
+                        self.out.write_char(decimal)?;
+                    }
+                    '0'..='9' if self.remaining > 0 => {
+                        self.out.write_char(c)?;
+                        self.remaining -= 1;
+                        if self.grouped && self.remaining > 0 && self.remaining.is_multiple_of(3) {
+                            self.out.write_str(thousands)?;
+                        }
+                    }
+                    c => self.out.write_char(c)?,
+                }
+            }
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0, false);
+    fmt::write(&mut count, number)?;
+    let separators = conventions.separators();
+    let mut group = Group {
+        out: f,
+        separators,
+        remaining: count.0,
+        grouped: count.0 >= separators.2,
+    };
+    fmt::write(&mut group, number)
+}
@@ -0,0 +1,140 @@
+// Furigana: readings displayed above the kanji of Japanese phrases.
+//
+// The readings are stored with the phrases ("Phrase::ruby"), as "[base|reading]" annotations.
+// "build.rs" checks them: every kanji has a reading, and the readings are kana. This module
+// turns a phrase into:
+//
+// - segments: the text split into (base, reading) parts,
+// - HTML "<ruby>" markup: <ruby>様<rp>(</rp><rt>さま</rt><rp>)</rp></ruby>,
+// - the plain-text bracket form: 様[さま],
+// - the kana rendering: さま.
+
+use core::fmt;
+
+use crate::catalog::Phrase;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment<'a> {
+    pub base: &'a str,
+    // "None" for the parts of the text that need no reading (kana, punctuation...).
+    pub reading: Option<&'a str>,
+}
+
+// Iterator returned by "segments()".
+#[derive(Clone, Debug)]
+pub struct Segments<'a> {
+    rest: &'a str,
+    // False for a phrase without annotation: its text is a single segment, even if it
+    // contains "[".
+    annotated: bool,
+}
+
+// Split a phrase into segments. A phrase without annotation is a single segment.
+pub fn segments(phrase: &Phrase) -> Segments<'static> {
+    Segments {
+        rest: phrase.ruby.unwrap_or(phrase.text),
+        annotated: phrase.ruby.is_some(),
+    }
+}
+
+impl<'a> Iterator for Segments<'a> {
+    type Item = Segment<'a>;
+
+    fn next(&mut self) -> Option<Segment<'a>> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        if !self.annotated {
+            let base = core::mem::take(&mut self.rest);
+            return Some(Segment { base, reading: None });
+        }
+        let annotation = self.rest.strip_prefix('[').and_then(|rest| {
+            let (annotation, rest) = rest.split_once(']')?;
+            let (base, reading) = annotation.split_once('|')?;
+            Some((Segment { base, reading: Some(reading) }, rest))
+        });
+        if let Some((segment, rest)) = annotation {
+            self.rest = rest;
+            return Some(segment);
+        }
+        // Plain text, up to the next annotation. A "[" that does not start an annotation
+        // cannot occur: "build.rs" rejects it.
+        let end = self
+            .rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '[')
+            .map(|(end, _)| end)
+            .unwrap_or(self.rest.len());
+        let (base, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(Segment { base, reading: None })
+    }
+}
+
+pub fn write_html<W: fmt::Write>(out: &mut W, phrase: &Phrase) -> fmt::Result {
+    for segment in segments(phrase) {
+        match segment.reading {
+            Some(reading) => {
+                out.write_str("<ruby>")?;
+                write_escaped_html(out, segment.base)?;
+                out.write_str("<rp>(</rp><rt>")?;
+                write_escaped_html(out, reading)?;
+                out.write_str("</rt><rp>)</rp></ruby>")?;
+            }
+            None => write_escaped_html(out, segment.base)?,
+        }
+    }
+    Ok(())
+}
+
+pub fn write_brackets<W: fmt::Write>(out: &mut W, phrase: &Phrase) -> fmt::Result {
+    for segment in segments(phrase) {
+        out.write_str(segment.base)?;
+        if let Some(reading) = segment.reading {
+            write!(out, "[{}]", reading)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn write_kana<W: fmt::Write>(out: &mut W, phrase: &Phrase) -> fmt::Result {
+    for segment in segments(phrase) {
+        out.write_str(segment.reading.unwrap_or(segment.base))?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+pub fn to_html(phrase: &Phrase) -> String {
+    let mut html = String::new();
+    write_html(&mut html, phrase).expect("writing into a String does not fail");
+    html
+}
+
+#[cfg(feature = "std")]
+pub fn to_brackets(phrase: &Phrase) -> String {
+    let mut text = String::new();
+    write_brackets(&mut text, phrase).expect("writing into a String does not fail");
+    text
+}
+
+#[cfg(feature = "std")]
+pub fn to_kana(phrase: &Phrase) -> String {
+    let mut text = String::new();
+    write_kana(&mut text, phrase).expect("writing into a String does not fail");
+    text
+}
+
+fn write_escaped_html<W: fmt::Write>(out: &mut W, text: &str) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '&' => out.write_str("&amp;")?,
+            '<' => out.write_str("&lt;")?,
+            '>' => out.write_str("&gt;")?,
+            '"' => out.write_str("&quot;")?,
+            c => out.write_char(c)?,
+        }
+    }
+    Ok(())
+}
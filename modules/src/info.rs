@@ -0,0 +1,160 @@
+// Description of the crate, so that deployments can check what they are running.
+
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::catalog::{self, Language};
+use crate::collation::Collator;
+
+// The features the crate was compiled with.
+const FEATURES: &[&str] = &[
+    #[cfg(feature = "std")]
+    "std",
+    #[cfg(feature = "metrics")]
+    "metrics",
+    #[cfg(feature = "lang-fr")]
+    "lang-fr",
+    #[cfg(feature = "lang-de")]
+    "lang-de",
+    #[cfg(feature = "lang-es")]
+    "lang-es",
+    #[cfg(feature = "lang-ko")]
+    "lang-ko",
+    #[cfg(feature = "lang-zh-hans")]
+    "lang-zh-hans",
+];
+
+#[derive(Clone, Copy, Debug)]
+pub struct CrateInfo {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub features: &'static [&'static str],
+    pub catalog_version: u32,
+    pub languages: &'static [&'static Language],
+    // Time of the last run of "build.rs", "YYYY-MM-DDTHH:MM:SSZ" (or "$SOURCE_DATE_EPOCH").
+    pub build_timestamp: &'static str,
+    // Commit the crate was built from, if it was built from a git repository.
+    pub git_hash: Option<&'static str>,
+}
+
+pub fn info() -> CrateInfo {
+    let git_hash = env!("MODULES_GIT_HASH");
+    CrateInfo {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        features: FEATURES,
+        catalog_version: catalog::VERSION,
+        languages: catalog::LANGUAGES,
+        build_timestamp: env!("MODULES_BUILD_TIMESTAMP"),
+        git_hash: if git_hash.is_empty() { None } else { Some(git_hash) },
+    }
+}
+
+impl CrateInfo {
+    // Number of phrases of a language, inherited phrases included.
+    pub fn phrase_count(language: &'static Language) -> usize {
+        language.entries().count()
+    }
+
+    // The languages sorted by name, in the English order (the names are English). Without
+    // allocation: each step looks for the smallest name after the previous one.
+    pub fn sorted_languages(&self) -> impl Iterator<Item = &'static Language> {
+        let collator = Collator::new("en");
+        let order = move |a: &Language, b: &Language| collator.compare(a.name, b.name).then_with(|| a.tag.cmp(b.tag));
+        let languages = self.languages;
+        let mut previous: Option<&'static Language> = None;
+        core::iter::from_fn(move || {
+            let next = languages
+                .iter()
+                .copied()
+                .filter(|language| match previous {
+                    Some(previous) => order(language, previous) == Ordering::Greater,
+                    None => true,
+                })
+                .min_by(|a, b| order(a, b))?;
+            previous = Some(next);
+            Some(next)
+        })
+    }
+
+    pub fn write_json<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        write!(out, "{{\"name\":")?;
+        write_json_string(out, self.name)?;
+        write!(out, ",\"version\":")?;
+        write_json_string(out, self.version)?;
+        write!(out, ",\"features\":[")?;
+        for (index, feature) in self.features.iter().enumerate() {
+            if index > 0 {
+                out.write_char(',')?;
+            }
+            write_json_string(out, feature)?;
+        }
+        write!(out, "],\"catalog_version\":{},\"languages\":[", self.catalog_version)?;
+        for (index, language) in self.sorted_languages().enumerate() {
+            if index > 0 {
+                out.write_char(',')?;
+            }
+            write!(out, "{{\"tag\":")?;
+            write_json_string(out, language.tag)?;
+            write!(out, ",\"name\":")?;
+            write_json_string(out, language.name)?;
+            write!(out, ",\"parent\":")?;
+            match language.parent {
+                Some(parent) => write_json_string(out, parent.tag)?,
+                None => out.write_str("null")?,
+            }
+            write!(out, ",\"phrases\":{}}}", CrateInfo::phrase_count(language))?;
+        }
+        write!(out, "],\"build_timestamp\":")?;
+        write_json_string(out, self.build_timestamp)?;
+        write!(out, ",\"git_hash\":")?;
+        match self.git_hash {
+            Some(hash) => write_json_string(out, hash)?,
+            None => out.write_str("null")?,
+        }
+        out.write_char('}')
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).expect("writing into a String does not fail");
+        json
+    }
+}
+
+// Human-readable form.
+impl fmt::Display for CrateInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {}", self.name, self.version)?;
+        write!(f, "features:")?;
+        for feature in self.features {
+            write!(f, " {}", feature)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "catalog version: {}", self.catalog_version)?;
+        writeln!(f, "built: {}", self.build_timestamp)?;
+        writeln!(f, "commit: {}", self.git_hash.unwrap_or("unknown"))?;
+        writeln!(f, "languages:")?;
+        for language in self.sorted_languages() {
+            writeln!(f, "  {}\t{} ({} phrases)", language.tag, language.name, CrateInfo::phrase_count(language))?;
+        }
+        Ok(())
+    }
+}
+
+fn write_json_string<W: fmt::Write>(out: &mut W, text: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in text.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
@@ -1,3 +0,0 @@
-pub fn say() {
-    println!("Farewell in Japanese!")
-}
\ No newline at end of file
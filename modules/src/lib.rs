@@ -1,3 +1,8 @@
+// Without the feature "std" (enabled by default), the crate does not use the standard library,
+// only "core": it can run on a microcontroller. Then, the phrases are available as "&'static str"
+// and nothing is printed or allocated.
+#![cfg_attr(not(feature = "std"), no_std)]
+
 // Note:
 // - When you use the "mod" keyword, you are creating a module, not importing a module.
 // - You must not declare the module "packages" here since, by convention, the file "lib.rs"
@@ -15,7 +20,108 @@ pub mod japanese;
 // The "pub" keyword makes the sub module "english" public.
 pub mod english;
 
-// The "pub" keyword makes the function "id()" public.
-pub fn id() {
-    println!("This is 'lib.rs'")
+// The optional languages ("french", "german"...) are only compiled with their feature
+// ("lang-fr", "lang-de"...). Their modules are entirely generated by "build.rs": the generated
+// file declares them, for example:
+//
+//     #[cfg(feature = "lang-fr")]
+//     pub mod french { ... }
+include!(concat!(env!("OUT_DIR"), "/packs.rs"));
+
+// The macro "phrase!()" gives the text of a phrase, checked at compile time: a typo in the
+// language or the phrase is a compilation error, not a lookup that fails at run time.
+//
+//     let text = modules::phrase!(ja, farewell.polite);
+//
+// It is generated by "build.rs" from "phrases.txt", like the language modules.
+include!(concat!(env!("OUT_DIR"), "/macros.rs"));
+
+// The errors of "phrase!()", checked by "cargo test" (each block must fail to compile; the first
+// one shows that the others fail because of the typo only).
+#[cfg(doctest)]
+#[doc = r#"
+```
+let _: &str = modules::phrase!(en, farewell.polite);
+```
+
+An unknown phrase:
+
+```compile_fail
+let _: &str = modules::phrase!(en, farwell.polite);
+```
+
+An unknown formality:
+
+```compile_fail
+let _: &str = modules::phrase!(en, farewell.formal);
+```
+
+An unknown language:
+
+```compile_fail
+let _: &str = modules::phrase!(xx, farewell.polite);
+```
+
+A malformed call:
+
+```compile_fail
+let _: &str = modules::phrase!("en", "farewell.polite");
+```
+"#]
+#[cfg_attr(
+    not(feature = "lang-fr"),
+    doc = r#"
+A language whose feature is disabled:
+
+```compile_fail
+let _: &str = modules::phrase!(fr, farewell.polite);
+```
+"#
+)]
+pub struct PhraseErrors;
+
+// The catalog gathers the phrases of all the languages.
+pub mod catalog;
+
+// The current locale of each thread.
+#[cfg(feature = "std")]
+pub mod locale;
+
+// Usage counters of the phrase lookups.
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+// Review workflow of the translations (status, translator, staleness).
+pub mod review;
+
+// Dates, times and numbers in the conventions of each language.
+pub mod format;
+
+// Locale-aware sorting of the listings.
+pub mod collation;
+
+// Readings of the kanji of Japanese phrases (ruby annotations).
+pub mod furigana;
+
+// Export and parsing of gettext PO files.
+#[cfg(feature = "std")]
+pub mod po;
+
+// Rendering of many records (CSV or JSON Lines) through the catalog.
+#[cfg(feature = "std")]
+pub mod batch;
+
+// Translation memory: fuzzy suggestions for the missing phrases.
+#[cfg(feature = "std")]
+pub mod memory;
+
+// Compact binary form of the catalog, with constant-time lookup.
+pub mod binary;
+
+// Description of the crate (version, features, languages...).
+pub mod info;
+
+// The "pub" keyword makes the function "info()" public.
+pub fn info() -> info::CrateInfo {
+    info::info()
 }
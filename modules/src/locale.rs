@@ -0,0 +1,93 @@
+// The current locale: the language used by the lookups that do not specify a language.
+//
+// Each thread has its own current locale. It can be changed for the duration of a closure
+// ("with_locale()") or of a future ("scope()"). The previous locale is restored afterwards,
+// even if the closure panics.
+
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use crate::catalog::{self, Formality, Language};
+use crate::english;
+use crate::format::Conventions;
+
+thread_local! {
+    static CURRENT: Cell<&'static Language> = const { Cell::new(&english::LANGUAGE) };
+}
+
+// Return the current locale of the calling thread.
+pub fn current() -> &'static Language {
+    CURRENT.with(Cell::get)
+}
+
+// Change the current locale of the calling thread, until it is changed again.
+// Unknown tags are resolved as described by "catalog::resolve()".
+pub fn set_current(tag: &str) {
+    CURRENT.with(|current| current.set(catalog::resolve(tag)));
+}
+
+// The formatting conventions (dates, numbers...) of the current locale of the calling thread.
+pub fn conventions() -> Conventions {
+    Conventions::of(current())
+}
+
+// Run "f" with the given current locale.
+pub fn with_locale<R>(tag: &str, f: impl FnOnce() -> R) -> R {
+    with_language(catalog::resolve(tag), f)
+}
+
+fn with_language<R>(language: &'static Language, f: impl FnOnce() -> R) -> R {
+    // The guard restores the previous locale when it is dropped, that is, when "f" returns
+    // or when the stack is unwound.
+    struct Restore(&'static Language);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|current| current.set(self.0));
+        }
+    }
+    let _restore = Restore(CURRENT.with(|current| current.replace(language)));
+    f()
+}
+
+// Run a future with the given current locale. The locale is set each time the future is polled,
+// thus it follows the task even if the executor moves it from one thread to another.
+pub fn scope<F: Future>(tag: &str, future: F) -> Scoped<F> {
+    Scoped {
+        language: catalog::resolve(tag),
+        future: Box::pin(future),
+    }
+}
+
+pub struct Scoped<F> {
+    language: &'static Language,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let language = self.language;
+        with_language(language, || self.future.as_mut().poll(cx))
+    }
+}
+
+// Look up a phrase in the current locale. If the current locale does not define the phrase, the
+// English phrase is returned.
+pub fn lookup(key: &str, formality: Formality) -> Option<&'static str> {
+    let language = current();
+    let phrase = language
+        .phrase(key, formality)
+        .or_else(|| english::LANGUAGE.phrase(key, formality))?;
+    Some(catalog::served(language, phrase))
+}
+
+pub fn greeting(formality: Formality) -> &'static str {
+    lookup(english::greetings::KEY, formality).unwrap_or(english::greetings::text(formality))
+}
+
+pub fn farewell(formality: Formality) -> &'static str {
+    lookup(english::farewells::KEY, formality).unwrap_or(english::farewells::text(formality))
+}
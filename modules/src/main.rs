@@ -5,14 +5,370 @@
 //   Cargo effectively treats "lib.rs" as the root of your crate, and "main.rs" as a separate
 //   binary that depends on your crate.
 
+// Clippy flags the optional line "use modules" below, kept on purpose (see the notes above).
+#![allow(clippy::single_component_path_imports)]
+
 use modules; // This line is optional since, by convention, "main.rs" **IS** the entry point
              // for the crate. Thus, there is no need to import the module.
 
-fn main() {
-    modules::id();
+use modules::batch::Format;
+use modules::catalog::Formality;
+use modules::collation::Collator;
+use modules::locale;
+use modules::memory::TranslationMemory;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {
+            demo();
+            ExitCode::SUCCESS
+        }
+        Some("compile") => compile(&args[1..]),
+        Some("info") => info(&args[1..]),
+        Some("furigana") => furigana(&args[1..]),
+        Some("review") => review(&args[1..]),
+        Some("export-po") => export_po(&args[1..]),
+        Some("check") => check(&args[1..]),
+        Some("suggest") => suggest(&args[1..]),
+        Some("list-languages") => list_languages(&args[1..]),
+        Some("dump") => dump(&args[1..]),
+        Some("batch") => batch(&args[1..]),
+        Some(command) => {
+            eprintln!("unknown command \"{}\"", command);
+            eprintln!("usage: modules [info [--json] | compile <output file> | list-languages [--locale <language>]");
+            eprintln!("               | dump <language> | export-po <language>");
+            eprintln!("               | batch [--format csv|jsonl] <input file> [<output file>]");
+            eprintln!("               | check <language> [<PO file>...] | suggest <language> <text> [<PO file>...]");
+            eprintln!("               | furigana <language> <key>.<formality> | review <language>]");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn demo() {
+    print!("{}", modules::info());
     modules::english::tell_slang();
     modules::english::farewells::say();
     modules::english::greetings::say();
     modules::japanese::farewells::say();
     modules::japanese::greetings::say();
+
+    // Without an explicit language, the lookups follow the current locale.
+    println!("{}", locale::greeting(Formality::Casual));
+    locale::with_locale("ja", || {
+        println!("{}", locale::greeting(Formality::Polite));
+        println!("{}", locale::farewell(Formality::Polite));
+    });
+    println!("{}", locale::farewell(Formality::Casual));
+
+    // Dates and numbers follow the conventions of the current locale. A fixed date keeps the
+    // output of the demo the same from one day to the next.
+    let date = modules::format::Date::new(2022, 3, 21).expect("2022-03-21 exists");
+    for tag in ["en", "ja"] {
+        locale::with_locale(tag, || {
+            let mut text = String::new();
+            modules::catalog::render(&mut text, "{greeting} {date}", &[
+                ("greeting", &locale::greeting(Formality::Polite)),
+                ("date", &locale::conventions().date(date)),
+            ])
+            .expect("writing into a String does not fail");
+            println!("{}", text);
+        });
+    }
+
+    // With "phrase!()", a typo in the language or the phrase does not compile.
+    println!("{}", modules::phrase!(en_gb, farewell.casual));
+}
+
+// Describe the crate, in JSON with "--json".
+fn info(args: &[String]) -> ExitCode {
+    match args {
+        [] => print!("{}", modules::info()),
+        [flag] if flag == "--json" => println!("{}", modules::info().to_json()),
+        _ => {
+            eprintln!("usage: modules info [--json]");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+// Print the languages of the catalog, sorted by name in the order of a locale (English by default).
+fn list_languages(args: &[String]) -> ExitCode {
+    let locale = match args {
+        [] => "en",
+        [flag, locale] if flag == "--locale" => locale.as_str(),
+        _ => {
+            eprintln!("usage: modules list-languages [--locale <language>]");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut languages = modules::catalog::LANGUAGES.to_vec();
+    Collator::new(locale).sort_by_key(&mut languages, |language| language.name);
+    for language in languages {
+        match language.parent {
+            Some(parent) => println!("{}\t{} (inherits from {})", language.tag, language.name, parent.tag),
+            None => println!("{}\t{}", language.tag, language.name),
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+// Print all the phrases of a language, sorted in the order of the language.
+fn dump(args: &[String]) -> ExitCode {
+    let [tag] = args else {
+        eprintln!("usage: modules dump <language>");
+        return ExitCode::FAILURE;
+    };
+    let language = match language(tag) {
+        Ok(language) => language,
+        Err(code) => return code,
+    };
+    let mut phrases: Vec<&modules::catalog::Phrase> = language.entries().collect();
+    Collator::new(language.tag).sort_by_key(&mut phrases, |phrase| phrase.text);
+    for phrase in phrases {
+        println!("{:<16} {}", format!("{}.{}", phrase.key, phrase.formality.as_str()), phrase.text);
+    }
+    ExitCode::SUCCESS
+}
+
+// Write the binary form of the catalog into a file.
+fn compile(args: &[String]) -> ExitCode {
+    let [path] = args else {
+        eprintln!("usage: modules compile <output file>");
+        return ExitCode::FAILURE;
+    };
+    let blob = match modules::binary::compile(modules::catalog::LANGUAGES, modules::catalog::VERSION) {
+        Ok(blob) => blob,
+        Err(error) => {
+            eprintln!("cannot compile the catalog: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = std::fs::write(path, &blob) {
+        eprintln!("cannot write \"{}\": {}", path, error);
+        return ExitCode::FAILURE;
+    }
+    println!("{} bytes written to \"{}\"", blob.len(), path);
+    ExitCode::SUCCESS
+}
+
+// Render the records of a CSV or JSON Lines file ("-" for the standard input). The results go to
+// the standard output, or to a file. Fails if a record fails, after all the records are rendered.
+fn batch(args: &[String]) -> ExitCode {
+    let usage = || {
+        eprintln!("usage: modules batch [--format csv|jsonl] <input file> [<output file>]");
+        ExitCode::FAILURE
+    };
+    let (format, paths) = match args {
+        [flag, format, paths @ ..] if flag == "--format" => match Format::parse(format) {
+            Some(format) => (Some(format), paths),
+            None => return usage(),
+        },
+        paths => (None, paths),
+    };
+    let (input, output) = match paths {
+        [input] => (input, None),
+        [input, output] => (input, Some(output)),
+        _ => return usage(),
+    };
+    let Some(format) = format.or_else(|| Format::from_path(input)) else {
+        eprintln!("unknown format of \"{}\": use \"--format csv\" or \"--format jsonl\"", input);
+        return ExitCode::FAILURE;
+    };
+    let reader: Box<dyn BufRead> = if input == "-" {
+        Box::new(std::io::stdin().lock())
+    } else {
+        match File::open(input) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(error) => {
+                eprintln!("cannot read \"{}\": {}", input, error);
+                return ExitCode::FAILURE;
+            }
+        }
+    };
+    let writer: Box<dyn Write> = match output {
+        None => Box::new(std::io::stdout().lock()),
+        Some(output) => match File::create(output) {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(error) => {
+                eprintln!("cannot write \"{}\": {}", output, error);
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    match modules::batch::run(reader, writer, format) {
+        Ok(summary) => {
+            eprintln!("{} record(s) rendered, {} error(s)", summary.records, summary.errors);
+            if summary.errors == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(error) => {
+            eprintln!("batch failed: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn language(tag: &str) -> Result<&'static modules::catalog::Language, ExitCode> {
+    modules::catalog::language(tag).ok_or_else(|| {
+        eprintln!("unknown language \"{}\" (see \"modules list-languages\")", tag);
+        ExitCode::FAILURE
+    })
+}
+
+// Print the PO file of a language, for translators.
+fn export_po(args: &[String]) -> ExitCode {
+    let [tag] = args else {
+        eprintln!("usage: modules export-po <language>");
+        return ExitCode::FAILURE;
+    };
+    match language(tag) {
+        Ok(language) => {
+            print!("{}", modules::po::export(language));
+            ExitCode::SUCCESS
+        }
+        Err(code) => code,
+    }
+}
+
+// Translation memory of a language: the catalog, plus the translated PO files.
+fn memory(language: &'static modules::catalog::Language, po_files: &[String]) -> Result<TranslationMemory, ExitCode> {
+    let mut memory = TranslationMemory::from_catalog(language);
+    for path in po_files {
+        let file = std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| modules::po::parse(&text).map_err(|error| error.to_string()));
+        match file {
+            Ok(file) => {
+                if let Some(other) = file.language.as_deref().filter(|other| !other.eq_ignore_ascii_case(language.tag)) {
+                    eprintln!("\"{}\" is a PO file for \"{}\", not \"{}\"", path, other, language.tag);
+                    return Err(ExitCode::FAILURE);
+                }
+                memory.import_po(&file);
+            }
+            Err(error) => {
+                eprintln!("cannot read \"{}\": {}", path, error);
+                return Err(ExitCode::FAILURE);
+            }
+        }
+    }
+    Ok(memory)
+}
+
+fn print_suggestions(memory: &TranslationMemory, source: &str) {
+    let suggestions = memory.suggest(source, 3);
+    if suggestions.is_empty() {
+        println!("    (no suggestion)");
+    }
+    for suggestion in suggestions {
+        println!("    {:>3}%  {}  ->  {}", suggestion.score, suggestion.unit.source, suggestion.unit.translation);
+    }
+}
+
+// Completeness check: list the phrases that a language lacks, with suggestions.
+fn check(args: &[String]) -> ExitCode {
+    let Some((tag, po_files)) = args.split_first() else {
+        eprintln!("usage: modules check <language> [<PO file>...]");
+        return ExitCode::FAILURE;
+    };
+    let (language, memory) = match language(tag).and_then(|language| Ok((language, memory(language, po_files)?))) {
+        Ok(found) => found,
+        Err(code) => return code,
+    };
+    let mut complete = true;
+    let mut missing: Vec<&modules::catalog::Phrase> = modules::catalog::missing(language).collect();
+    Collator::new(modules::catalog::LANGUAGES[0].tag).sort_by_key(&mut missing, |phrase| phrase.text);
+    for phrase in missing {
+        complete = false;
+        println!("missing {}.{}: {}", phrase.key, phrase.formality.as_str(), phrase.text);
+        print_suggestions(&memory, phrase.text);
+    }
+    if complete {
+        println!("\"{}\" defines all the phrases", language.tag);
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// Suggest translations for a source text.
+fn suggest(args: &[String]) -> ExitCode {
+    let [tag, text, po_files @ ..] = args else {
+        eprintln!("usage: modules suggest <language> <text> [<PO file>...]");
+        return ExitCode::FAILURE;
+    };
+    match language(tag).and_then(|language| memory(language, po_files)) {
+        Ok(memory) => {
+            print_suggestions(&memory, text);
+            ExitCode::SUCCESS
+        }
+        Err(code) => code,
+    }
+}
+
+// Print a Japanese phrase with the readings of its kanji.
+fn furigana(args: &[String]) -> ExitCode {
+    let [tag, id] = args else {
+        eprintln!("usage: modules furigana <language> <key>.<formality>");
+        return ExitCode::FAILURE;
+    };
+    let language = match language(tag) {
+        Ok(language) => language,
+        Err(code) => return code,
+    };
+    let Some(phrase) = modules::catalog::parse_id(id).and_then(|(key, formality)| language.phrase(key, formality)) else {
+        eprintln!("unknown phrase \"{}\" (expected \"<key>.<formality>\", like \"farewell.polite\")", id);
+        return ExitCode::FAILURE;
+    };
+    println!("text:     {}", phrase.text);
+    println!("brackets: {}", modules::furigana::to_brackets(phrase));
+    println!("kana:     {}", modules::furigana::to_kana(phrase));
+    println!("html:     {}", modules::furigana::to_html(phrase));
+    for segment in modules::furigana::segments(phrase) {
+        println!("segment:  {:?} {:?}", segment.base, segment.reading);
+    }
+    ExitCode::SUCCESS
+}
+
+// Print the review metadata of the translations of a language.
+fn review(args: &[String]) -> ExitCode {
+    let [tag] = args else {
+        eprintln!("usage: modules review <language>");
+        return ExitCode::FAILURE;
+    };
+    let language = match language(tag) {
+        Ok(language) => language,
+        Err(code) => return code,
+    };
+    let mut phrases: Vec<(String, &modules::catalog::Phrase)> = language
+        .phrases
+        .iter()
+        .map(|phrase| (format!("{}.{}", phrase.key, phrase.formality.as_str()), phrase))
+        .collect();
+    Collator::new("en").sort_by_key(&mut phrases, |(id, _)| id);
+    for (id, phrase) in phrases {
+        match phrase.review {
+            Some(review) => {
+                let line = format!(
+                    "{:<16} {:<10} {:<20} {:<20} {}",
+                    id,
+                    review.status.as_str(),
+                    review.translator,
+                    review.timestamp,
+                    if phrase.is_stale() { "STALE" } else { "" }
+                );
+                println!("{}", line.trim_end());
+            }
+            None => println!("{:<16} source", id),
+        }
+    }
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,182 @@
+// Translation memory: suggestions for the phrases that a language does not define yet.
+//
+// The memory stores (source, translation) pairs, taken from the catalog or imported from PO
+// files. Given a new source text, it returns the stored pairs whose source looks like it,
+// ranked by a match percentage. The percentage is the average of:
+//
+// - the edit distance similarity: 1 - (Levenshtein distance / length of the longest text),
+// - the token overlap: |common tokens| / |all tokens| (Jaccard index).
+//
+// Tokens are lowercased words; ideographs and kana are tokens on their own, since Japanese
+// does not separate words with spaces. An inverted index (token -> pairs) selects the
+// candidates, so that only the pairs that share at least one token are compared.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::catalog::{self, Language};
+use crate::po;
+
+// Suggestions under this percentage are not returned.
+pub const MIN_SCORE: u8 = 30;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unit {
+    // Phrase identifier ("farewell.polite") or PO context, if known.
+    pub context: Option<String>,
+    pub source: String,
+    pub translation: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion<'a> {
+    pub unit: &'a Unit,
+    // Match percentage, 100 for an identical source.
+    pub score: u8,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TranslationMemory {
+    units: Vec<Unit>,
+    index: BTreeMap<String, Vec<usize>>,
+}
+
+impl TranslationMemory {
+    pub fn new() -> TranslationMemory {
+        TranslationMemory::default()
+    }
+
+    // Memory of the phrases of "target", translated from the source language of the catalog.
+    pub fn from_catalog(target: &'static Language) -> TranslationMemory {
+        let mut memory = TranslationMemory::new();
+        for phrase in catalog::LANGUAGES[0].phrases {
+            if let Some(translation) = target.phrase(phrase.key, phrase.formality) {
+                memory.insert(Unit {
+                    context: Some(format!("{}.{}", phrase.key, phrase.formality.as_str())),
+                    source: phrase.text.to_string(),
+                    translation: translation.text.to_string(),
+                });
+            }
+        }
+        memory
+    }
+
+    // Import the translated entries of a PO file, except the fuzzy ones (their translations are
+    // guesses). Return the number of imported entries.
+    pub fn import_po(&mut self, file: &po::File) -> usize {
+        let mut count = 0;
+        for entry in file.entries.iter().filter(|entry| !entry.translation.is_empty() && !entry.fuzzy) {
+            self.insert(Unit {
+                context: entry.context.clone(),
+                source: entry.source.clone(),
+                translation: entry.translation.clone(),
+            });
+            count += 1;
+        }
+        count
+    }
+
+    // Add a pair. An identical pair is only stored once.
+    pub fn insert(&mut self, unit: Unit) {
+        if self
+            .units
+            .iter()
+            .any(|other| other.source == unit.source && other.translation == unit.translation)
+        {
+            return;
+        }
+        let position = self.units.len();
+        for token in tokens(&unit.source) {
+            self.index.entry(token).or_default().push(position);
+        }
+        self.units.push(unit);
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    // Return at most "limit" suggestions for a source text, best first.
+    pub fn suggest(&self, source: &str, limit: usize) -> Vec<Suggestion<'_>> {
+        let source_tokens = tokens(source);
+        let candidates: BTreeSet<usize> = source_tokens
+            .iter()
+            .filter_map(|token| self.index.get(token))
+            .flatten()
+            .copied()
+            .collect();
+        let mut suggestions: Vec<Suggestion> = candidates
+            .into_iter()
+            .map(|position| {
+                let unit = &self.units[position];
+                Suggestion {
+                    unit,
+                    score: score(source, &source_tokens, &unit.source),
+                }
+            })
+            .filter(|suggestion| suggestion.score >= MIN_SCORE)
+            .collect();
+        suggestions.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.unit.source.cmp(&b.unit.source)));
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+fn score(source: &str, source_tokens: &BTreeSet<String>, candidate: &str) -> u8 {
+    if source == candidate {
+        return 100;
+    }
+    let longest = source.chars().count().max(candidate.chars().count());
+    let similarity = 1.0 - levenshtein(source, candidate) as f64 / longest as f64;
+    let candidate_tokens = tokens(candidate);
+    let common = source_tokens.intersection(&candidate_tokens).count();
+    let all = source_tokens.union(&candidate_tokens).count();
+    let overlap = if all == 0 { 0.0 } else { common as f64 / all as f64 };
+    // An identical source is the only 100% match.
+    (((similarity + overlap) / 2.0 * 100.0).round() as u8).min(99)
+}
+
+// Number of character insertions, deletions and substitutions that turn "a" into "b".
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current: Vec<usize> = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != *cb) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+fn tokens(text: &str) -> BTreeSet<String> {
+    let mut tokens = BTreeSet::new();
+    let mut word = String::new();
+    for c in text.chars() {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.insert(std::mem::take(&mut word));
+            }
+            tokens.insert(c.to_string());
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            tokens.insert(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        tokens.insert(word);
+    }
+    tokens
+}
+
+// Kana, CJK ideographs and Hangul syllables.
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}')
+}
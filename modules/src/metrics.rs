@@ -0,0 +1,124 @@
+// Usage counters of the phrase lookups (feature "metrics").
+//
+// There is one counter per language, phrase key, formality and "fallback hit" (the phrase was
+// not defined by the requested language: it was inherited from the base language of a regional
+// variant, or taken from English). The counters are atomic integers allocated at compile time:
+// counting a lookup neither locks nor allocates.
+//
+// Without the feature "metrics", this module does not exist and the lookups are not counted.
+//
+// Only the lookups by tag at run time are counted: "catalog::lookup()", "locale::lookup()" (and
+// "locale::greeting()"...) and "review::lookup_approved()". The phrases read directly are not:
+// "phrase!()", the generated constants and "text()" functions ("japanese::greetings::POLITE"),
+// "Language::phrase()" and "Language::get()". Most of them are resolved at compile time, and
+// "Language::phrase()" is also used by the catalog itself (completeness check, exports...).
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::catalog::{Formality, Language, Phrase, KEYS, LANGUAGES, LANGUAGE_COUNT};
+
+const FORMALITY_COUNT: usize = Formality::ALL.len();
+const COUNTER_COUNT: usize = LANGUAGE_COUNT * KEYS.len() * FORMALITY_COUNT * 2;
+
+static COUNTERS: [AtomicU64; COUNTER_COUNT] = [const { AtomicU64::new(0) }; COUNTER_COUNT];
+
+pub(crate) fn record(language: &'static Language, phrase: &'static Phrase) {
+    // A language built outside of the catalog has no counter.
+    if !LANGUAGES.get(language.index).is_some_and(|other| core::ptr::eq(*other, language)) {
+        return;
+    }
+    // The phrases of the language itself are in its slice, the other ones are fallbacks.
+    let fallback = !language.phrases.as_ptr_range().contains(&(phrase as *const Phrase));
+    let index = ((language.index * KEYS.len() + phrase.checked_key as usize) * FORMALITY_COUNT
+        + phrase.formality as usize)
+        * 2
+        + fallback as usize;
+    COUNTERS[index].fetch_add(1, Ordering::Relaxed);
+}
+
+// Set all the counters to zero.
+pub fn reset() {
+    for counter in &COUNTERS {
+        counter.store(0, Ordering::Relaxed);
+    }
+}
+
+// Copy of the counters at a given time.
+pub fn snapshot() -> Snapshot {
+    Snapshot {
+        counts: core::array::from_fn(|index| COUNTERS[index].load(Ordering::Relaxed)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub language: &'static str,
+    pub key: &'static str,
+    pub formality: Formality,
+    pub fallback: bool,
+    pub count: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    counts: [u64; COUNTER_COUNT],
+}
+
+impl Snapshot {
+    // The counters that are not zero.
+    pub fn samples(&self) -> impl Iterator<Item = Sample> + '_ {
+        self.counts.iter().enumerate().filter(|(_, count)| **count > 0).map(|(index, count)| {
+            let fallback = index % 2 == 1;
+            let index = index / 2;
+            let formality = Formality::ALL[index % FORMALITY_COUNT];
+            let index = index / FORMALITY_COUNT;
+            Sample {
+                language: LANGUAGES[index / KEYS.len()].tag,
+                key: KEYS[index % KEYS.len()],
+                formality,
+                fallback,
+                count: *count,
+            }
+        })
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+// Prometheus text exposition format.
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# HELP modules_phrase_lookups_total Number of phrase lookups served by the catalog.")?;
+        writeln!(f, "# TYPE modules_phrase_lookups_total counter")?;
+        for sample in self.samples() {
+            write!(f, "modules_phrase_lookups_total{{language=\"")?;
+            write_label(f, sample.language)?;
+            write!(f, "\",key=\"")?;
+            write_label(f, sample.key)?;
+            writeln!(
+                f,
+                "\",formality=\"{}\",fallback=\"{}\"}} {}",
+                sample.formality.as_str(),
+                sample.fallback,
+                sample.count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Label values escape "\", """ and line feeds.
+fn write_label(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            '\\' => f.write_str("\\\\")?,
+            '"' => f.write_str("\\\"")?,
+            '\n' => f.write_str("\\n")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    Ok(())
+}
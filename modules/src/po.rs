@@ -0,0 +1,218 @@
+// Gettext PO files: export of the catalog for translators, and parsing of translated files.
+//
+// Each phrase becomes an entry whose context ("msgctxt") is the phrase identifier
+// ("farewell.polite"), whose "msgid" is the text in the source language and whose "msgstr" is
+// the translation (empty if the language does not define the phrase yet).
+//
+// The parser reads the entries of a single text ("msgid"/"msgstr"): the plural forms
+// ("msgid_plural", "msgstr[n]") are rejected. The flags comment ("#, fuzzy") is read, the other
+// comments are ignored.
+
+use std::fmt;
+use std::fmt::Write as _;
+
+use crate::catalog::{Language, LANGUAGES};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub context: Option<String>,
+    pub source: String,
+    pub translation: String,
+    // Flagged "#, fuzzy": the translation is a guess (of "msgmerge" for example), to be reviewed.
+    pub fuzzy: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// A parsed PO file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct File {
+    // Value of the "Language" header field, if any.
+    pub language: Option<String>,
+    pub entries: Vec<Entry>,
+}
+
+// Export the phrases of a language, translated from the source language (the first language
+// of the catalog).
+pub fn export(language: &'static Language) -> String {
+    let source = LANGUAGES[0];
+    let mut out = String::new();
+    writeln!(out, "# Phrases of the \"modules\" catalog: {} -> {}.", source.tag, language.tag).unwrap();
+    writeln!(out, "msgid \"\"").unwrap();
+    writeln!(out, "msgstr \"\"").unwrap();
+    writeln!(out, "\"Language: {}\\n\"", language.tag).unwrap();
+    writeln!(out, "\"Content-Type: text/plain; charset=UTF-8\\n\"").unwrap();
+    for phrase in source.phrases {
+        let translation = language
+            .phrase(phrase.key, phrase.formality)
+            .map(|translation| translation.text)
+            .unwrap_or("");
+        writeln!(out).unwrap();
+        writeln!(out, "msgctxt \"{}.{}\"", phrase.key, phrase.formality.as_str()).unwrap();
+        writeln!(out, "msgid \"{}\"", quote(phrase.text)).unwrap();
+        writeln!(out, "msgstr \"{}\"", quote(translation)).unwrap();
+    }
+    out
+}
+
+pub fn parse(text: &str) -> Result<File, Error> {
+    // The field being read, and the entry being built.
+    #[derive(Clone, Copy, PartialEq)]
+    enum Field {
+        None,
+        Context,
+        Source,
+        Translation,
+    }
+    let mut file = File::default();
+    let mut field = Field::None;
+    let mut context: Option<String> = None;
+    let mut source: Option<String> = None;
+    let mut translation: Option<String> = None;
+    // The flags comment precedes the entry it applies to.
+    let mut fuzzy = false;
+
+    let mut flush = |context: &mut Option<String>,
+                     source: &mut Option<String>,
+                     translation: &mut Option<String>,
+                     fuzzy: &mut bool,
+                     line: usize|
+     -> Result<(), Error> {
+        match (source.take(), translation.take()) {
+            (None, None) => {
+                if context.is_some() {
+                    return Err(Error { line, message: "\"msgctxt\" without \"msgid\"".to_string() });
+                }
+            }
+            (Some(source), Some(translation)) => {
+                if source.is_empty() {
+                    // The header: "Field: value\n" lines.
+                    file.language = translation
+                        .lines()
+                        .find_map(|line| line.strip_prefix("Language:"))
+                        .map(|language| language.trim().to_string());
+                } else {
+                    file.entries.push(Entry { context: context.clone(), source, translation, fuzzy: *fuzzy });
+                }
+                // The next flags are those of the next entry.
+                *fuzzy = false;
+            }
+            (Some(_), None) => return Err(Error { line, message: "\"msgid\" without \"msgstr\"".to_string() }),
+            (None, Some(_)) => return Err(Error { line, message: "\"msgstr\" without \"msgid\"".to_string() }),
+        }
+        *context = None;
+        Ok(())
+    };
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if let Some(flags) = line.strip_prefix("#,") {
+            // The entry before is complete.
+            if source.is_some() {
+                flush(&mut context, &mut source, &mut translation, &mut fuzzy, number)?;
+            }
+            fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = match line.split_once(' ') {
+            Some((keyword, rest)) if !line.starts_with('"') => (keyword, rest.trim()),
+            _ => ("", line),
+        };
+        let value = unquote(rest).map_err(|message| Error { line: number, message })?;
+        match keyword {
+            "msgctxt" => {
+                flush(&mut context, &mut source, &mut translation, &mut fuzzy, number)?;
+                context = Some(value);
+                field = Field::Context;
+            }
+            "msgid" => {
+                if source.is_some() {
+                    flush(&mut context, &mut source, &mut translation, &mut fuzzy, number)?;
+                }
+                source = Some(value);
+                field = Field::Source;
+            }
+            "msgstr" => {
+                if source.is_none() || translation.is_some() {
+                    return Err(Error { line: number, message: "unexpected \"msgstr\"".to_string() });
+                }
+                translation = Some(value);
+                field = Field::Translation;
+            }
+            // Continuation of the previous string.
+            "" => match field {
+                Field::Context => context.get_or_insert_with(String::new).push_str(&value),
+                Field::Source => source.get_or_insert_with(String::new).push_str(&value),
+                Field::Translation => translation.get_or_insert_with(String::new).push_str(&value),
+                Field::None => {
+                    return Err(Error { line: number, message: "string outside of an entry".to_string() })
+                }
+            },
+            keyword if keyword == "msgid_plural" || keyword.starts_with("msgstr[") => {
+                let message = format!("plural forms are not supported (\"{}\")", keyword);
+                return Err(Error { line: number, message });
+            }
+            keyword => {
+                return Err(Error { line: number, message: format!("unsupported keyword \"{}\"", keyword) })
+            }
+        }
+    }
+    flush(&mut context, &mut source, &mut translation, &mut fuzzy, text.lines().count())?;
+    Ok(file)
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted
+}
+
+fn unquote(text: &str) -> Result<String, String> {
+    let inner = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .filter(|_| text.len() >= 2)
+        .ok_or_else(|| format!("expected a quoted string, found {}", text))?;
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('r') => value.push('\r'),
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                Some(c) => return Err(format!("unknown escape sequence \"\\{}\"", c)),
+                None => return Err("unterminated string".to_string()),
+            },
+            '"' => return Err("unescaped quote inside a string".to_string()),
+            c => value.push(c),
+        }
+    }
+    Ok(value)
+}
@@ -0,0 +1,89 @@
+// Review workflow of the translations.
+//
+// Each translation records its status, its translator, the date of its last change and the
+// hash of the source text it was translated from (see "phrases.txt"). The phrases of the source
+// language have no review metadata: they are the reference.
+
+use crate::catalog::{self, Formality, Language, Phrase, LANGUAGES};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    Draft,
+    Translated,
+    Reviewed,
+    Approved,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Draft => "draft",
+            Status::Translated => "translated",
+            Status::Reviewed => "reviewed",
+            Status::Approved => "approved",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Review {
+    pub status: Status,
+    pub translator: &'static str,
+    // "YYYY-MM-DD" or "YYYY-MM-DDTHH:MM:SSZ".
+    pub timestamp: &'static str,
+    // "source_hash()" of the source text, at the time of the translation.
+    pub source_hash: u32,
+}
+
+// FNV-1a (32 bits) of a text.
+pub const fn source_hash(text: &str) -> u32 {
+    let bytes = text.as_bytes();
+    let mut hash: u32 = 0x811c_9dc5;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash = (hash ^ bytes[index] as u32).wrapping_mul(0x0100_0193);
+        index += 1;
+    }
+    hash
+}
+
+impl Phrase {
+    // True for the phrases of the source language and for the approved translations.
+    pub fn is_approved(&self) -> bool {
+        self.review.is_none_or(|review| review.status == Status::Approved)
+    }
+
+    // True if the source text changed since the phrase was translated.
+    pub fn is_stale(&self) -> bool {
+        let Some(review) = self.review else {
+            return false;
+        };
+        LANGUAGES[0]
+            .phrase(self.key, self.formality)
+            .is_some_and(|source| source_hash(source.text) != review.source_hash)
+    }
+}
+
+// The stale translations of a language (inherited phrases excluded).
+pub fn stale(language: &'static Language) -> impl Iterator<Item = &'static Phrase> {
+    language.phrases.iter().filter(|phrase| phrase.is_stale())
+}
+
+// Like "catalog::lookup()", but skip the translations that are not approved: the base language
+// of a regional variant is tried next, then the source language.
+pub fn lookup_approved(tag: &str, key: &str, formality: Formality) -> Option<&'static str> {
+    let requested = catalog::language(tag)?;
+    let mut language = Some(requested);
+    while let Some(current) = language {
+        if let Some(phrase) = current
+            .phrases
+            .iter()
+            .find(|phrase| phrase.key == key && phrase.formality == formality && phrase.is_approved())
+        {
+            return Some(catalog::served(requested, phrase));
+        }
+        language = current.parent;
+    }
+    let source = LANGUAGES[0].phrase(key, formality)?;
+    Some(catalog::served(requested, source))
+}
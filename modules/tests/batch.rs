@@ -0,0 +1,141 @@
+// Batch rendering: the CSV and JSON Lines readers, on valid and invalid records.
+
+#![cfg(feature = "std")]
+
+use modules::batch::{self, Format, Summary};
+
+fn run(input: &str, format: Format) -> (String, Summary) {
+    let mut output = Vec::new();
+    let summary = batch::run(input.as_bytes(), &mut output, format).unwrap();
+    (String::from_utf8(output).unwrap(), summary)
+}
+
+// The "text" or "error" of each output line of JSON Lines.
+fn results(input: &str) -> Vec<String> {
+    let (output, _) = run(input, Format::JsonLines);
+    output
+        .lines()
+        .map(|line| {
+            let (_, result) = line.split_once(",\"text\":").unwrap();
+            result.strip_suffix('}').unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn csv_records() {
+    let input = "key,language,name\n\
+                 welcome.casual,en,Yuki\n\
+                 \n\
+                 welcome.casual,ja,\"Ann, \"\"the\"\"\n\
+                 Second\"\n\
+                 welcome.casual,en,\n\
+                 welcome.casual,xx,Yuki\n\
+                 farewell.casual,en\n\
+                 welcome.casual,en,\"unterminated\n";
+    let (output, summary) = run(input, Format::Csv);
+    let expected = "line,key,language,text,error\n\
+                    2,welcome.casual,en,\"Welcome, Yuki!\",\n\
+                    4,welcome.casual,ja,\"Ann, \"\"the\"\"\nSecond、いらっしゃい！\",\n\
+                    6,welcome.casual,en,,\"no value for the placeholder \"\"{name}\"\"\"\n\
+                    7,welcome.casual,xx,,\"unknown language \"\"xx\"\"\"\n\
+                    8,,,,\"2 fields, but the header has 3 columns\"\n\
+                    9,,,,unterminated quoted field\n";
+    assert_eq!(output, expected);
+    assert_eq!(summary, Summary { records: 6, errors: 4 });
+}
+
+#[test]
+fn csv_header_must_name_the_columns() {
+    let (output, summary) = run("phrase,lang\nwelcome.casual,en\n", Format::Csv);
+    assert_eq!(output, "line,key,language,text,error\n1,,,,\"the header must have the columns \"\"key\"\" and \"\"language\"\"\"\n");
+    assert_eq!(summary, Summary { records: 1, errors: 1 });
+    assert_eq!(run("", Format::Csv).1, Summary::default());
+}
+
+#[test]
+fn json_lines_records() {
+    let input = r#"{"key": "welcome.casual", "language": "en", "args": {"name": "Yuki"}}
+
+{"key":"welcome.casual","language":"en","args":{"name":1234}}
+{"key": "welcome.casual", "language": "en", "args": {"name": -2.50}}
+{"key": "welcome.casual", "language": "en", "args": {"name": 1e3}}
+{"key": "welcome.casual", "language": "en", "args": {"name": "é😀\n"}}
+"#;
+    let (output, summary) = run(input, Format::JsonLines);
+    assert_eq!(summary, Summary { records: 5, errors: 0 });
+    let first = output.lines().next().unwrap();
+    assert_eq!(first, r#"{"line":1,"key":"welcome.casual","language":"en","text":"Welcome, Yuki!","error":null}"#);
+    assert_eq!(
+        results(input),
+        [
+            r#""Welcome, Yuki!","error":null"#,
+            r#""Welcome, 1,234!","error":null"#,
+            r#""Welcome, -2.50!","error":null"#,
+            r#""Welcome, 1e3!","error":null"#,
+            r#""Welcome, é😀\n!","error":null"#,
+        ]
+    );
+}
+
+#[test]
+fn invalid_json_is_reported() {
+    let cases = [
+        (r#"{"key": "welcome.casual", "language": "en""#, "expected \\\",\\\" or \\\"}\\\" (column 43)"),
+        (r#"["welcome.casual", "en"]"#, "expected a JSON object"),
+        (r#"{"key": 1, "language": "en"}"#, "\\\"key\\\" must be a string"),
+        (r#"{"key": "welcome.casual"}"#, "missing field \\\"language\\\""),
+        (r#"{"key": "welcome.casual", "language": "en", "other": null}"#, "unknown field \\\"other\\\""),
+        (r#"{"key": "welcome.casual", "language": "en"} x"#, "unexpected characters after the JSON value (column 45)"),
+        (r#"{"key": "a\q"}"#, "unknown escape \\\"\\\\q\\\" (column 13)"),
+        (r#"{"key": "a	b"}"#, "control character in a string (column 12)"),
+        // "from_str_radix()" accepts a sign: "\u+041" is not "A".
+        (r#"{"key": "\u+041"}"#, "invalid \\\"\\\\u\\\" escape (column 12)"),
+        (r#"{"key": "\ud83dA"}"#, "invalid \\\"\\\\u\\\" escape (column 16)"),
+        (r#"{"key": "\u00"}"#, "invalid \\\"\\\\u\\\" escape (column 12)"),
+    ];
+    for (input, error) in cases {
+        let result = &results(input)[0];
+        assert_eq!(result, &format!("null,\"error\":\"{}\"", error), "{}", input);
+    }
+}
+
+#[test]
+fn numbers_follow_the_json_grammar() {
+    let number = |number: &str| {
+        let input = format!(r#"{{"key": "welcome.casual", "language": "en", "args": {{"name": {}}}}}"#, number);
+        results(&input).remove(0)
+    };
+    for valid in ["0", "-0", "7", "10", "-1.5", "0.25", "1e5", "1E+5", "2.5e-3", "-0.0e0"] {
+        assert!(number(valid).ends_with("\"error\":null"), "{}: {}", valid, number(valid));
+    }
+    for invalid in ["01", "-01", "1.", ".5", "-", "1e", "1e+", "+1", "1.5.2", "--1", "1-2", "0x10"] {
+        let result = number(invalid);
+        assert!(result.contains("invalid number") || result.contains("expected"), "{}: {}", invalid, result);
+    }
+    assert_eq!(number("01"), r#"null,"error":"invalid number (column 62)""#);
+    assert_eq!(number("1."), r#"null,"error":"invalid number (column 62)""#);
+}
+
+#[test]
+fn deep_nesting_only_fails_its_record() {
+    let deep = "[".repeat(100_000);
+    let input = format!("{}\n{{\"key\": \"welcome.casual\", \"language\": \"en\", \"args\": {{\"name\": \"Yuki\"}}}}\n", deep);
+    let (_, summary) = run(&input, Format::JsonLines);
+    assert_eq!(summary, Summary { records: 2, errors: 1 });
+    assert_eq!(
+        results(&input),
+        [r#"null,"error":"arrays and objects nested too deeply (column 129)""#, r#""Welcome, Yuki!","error":null"#]
+    );
+    // 128 levels are parsed: the record, "args" and 126 arrays (then rejected as an argument).
+    let nested = |depth: usize| {
+        let input = format!(
+            r#"{{"key": "welcome.casual", "language": "en", "args": {{"name": "Yuki", "other": {}{}}}}}"#,
+            "[".repeat(depth),
+            "]".repeat(depth)
+        );
+        results(&input).remove(0)
+    };
+    assert_eq!(nested(126), r#"null,"error":"the value of \"other\" must be a string or a number""#);
+    assert!(nested(127).contains("nested too deeply"), "{}", nested(127));
+}
@@ -0,0 +1,130 @@
+// The binary form of the catalog: round trip through "compile()" and "CompiledCatalog", and the
+// checks of a damaged blob.
+
+#![cfg(feature = "std")]
+
+use modules::binary::{self, CompiledCatalog, Error};
+use modules::catalog::{self, Formality, Language, LANGUAGES};
+
+fn blob() -> Vec<u8> {
+    binary::compile(LANGUAGES, catalog::VERSION).unwrap()
+}
+
+// The CRC-32 of the body, written at the end of the header (bit by bit, as the specification
+// defines it).
+fn fix_checksum(blob: &mut [u8]) {
+    let mut crc: u32 = 0xffff_ffff;
+    for byte in &blob[28..] {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    blob[24..28].copy_from_slice(&(!crc).to_le_bytes());
+}
+
+fn read_u32(blob: &[u8], offset: usize) -> usize {
+    u32::from_le_bytes(blob[offset..offset + 4].try_into().unwrap()) as usize
+}
+
+#[test]
+fn every_phrase_is_found() {
+    let blob = blob();
+    let compiled = CompiledCatalog::from_bytes(&blob).unwrap();
+    assert_eq!(compiled.catalog_version(), catalog::VERSION);
+    let mut count = 0;
+    for language in LANGUAGES {
+        for phrase in language.entries() {
+            assert_eq!(compiled.lookup(language.tag, phrase.key, phrase.formality), Some(phrase.text));
+            count += 1;
+        }
+    }
+    assert_eq!(compiled.len(), count);
+    assert_eq!(compiled.lookup("en", "no such phrase", Formality::Casual), None);
+    assert_eq!(compiled.lookup("xx", "greeting", Formality::Casual), None);
+}
+
+#[test]
+fn tags_are_case_insensitive() {
+    let blob = blob();
+    let compiled = CompiledCatalog::from_bytes(&blob).unwrap();
+    for language in LANGUAGES {
+        let phrase = language.entries().next().unwrap();
+        for tag in [language.tag.to_ascii_lowercase(), language.tag.to_ascii_uppercase()] {
+            assert_eq!(compiled.lookup(&tag, phrase.key, phrase.formality), Some(phrase.text), "{}", tag);
+        }
+    }
+    // The keys are not.
+    let phrase = LANGUAGES[0].entries().next().unwrap();
+    assert_eq!(compiled.lookup("en", &phrase.key.to_ascii_uppercase(), phrase.formality), None);
+}
+
+#[test]
+fn empty_catalog() {
+    let blob = binary::compile(&[], 7).unwrap();
+    let compiled = CompiledCatalog::from_bytes(&blob).unwrap();
+    assert!(compiled.is_empty());
+    assert_eq!(compiled.catalog_version(), 7);
+    assert_eq!(compiled.lookup("en", "greeting", Formality::Casual), None);
+}
+
+#[test]
+fn duplicate_identifiers_are_rejected() {
+    let english = catalog::language("en").unwrap();
+    let error = binary::compile(&[english, english], 1).unwrap_err();
+    assert!(matches!(error, Error::DuplicateIdentifier { tag: "en", .. }), "{:?}", error);
+    // The tags are case-insensitive.
+    let upper: &'static Language = Box::leak(Box::new(Language { tag: "EN", ..*english }));
+    let error = binary::compile(&[english, upper], 1).unwrap_err();
+    assert!(matches!(error, Error::DuplicateIdentifier { tag: "EN", .. }), "{:?}", error);
+}
+
+#[test]
+fn damaged_headers_are_rejected() {
+    let blob = blob();
+    assert_eq!(CompiledCatalog::from_bytes(&blob[..27]).unwrap_err(), Error::Truncated);
+    assert_eq!(CompiledCatalog::from_bytes(&blob[..blob.len() - 1]).unwrap_err(), Error::Truncated);
+    let mut damaged = blob.clone();
+    damaged[0] = b'X';
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::BadMagic);
+    let mut damaged = blob.clone();
+    damaged[4..6].copy_from_slice(&1u16.to_le_bytes());
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::UnsupportedVersion(1));
+    // A number of entries that does not match the size of the body.
+    let mut damaged = blob.clone();
+    damaged[12] ^= 1;
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::Truncated);
+    let mut damaged = blob.clone();
+    damaged[12..28].copy_from_slice(&[0xff; 16]);
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::Truncated);
+}
+
+#[test]
+fn damaged_bodies_are_rejected() {
+    let blob = blob();
+    // Any flipped bit of the body.
+    for offset in (28..blob.len()).step_by(97) {
+        let mut damaged = blob.clone();
+        damaged[offset] ^= 0x10;
+        match CompiledCatalog::from_bytes(&damaged) {
+            Err(Error::ChecksumMismatch { expected, actual }) => assert_ne!(expected, actual),
+            other => panic!("{:?}", other),
+        }
+    }
+    // With a valid checksum: an entry outside of the string pool, and a text that is not UTF-8.
+    let entries = 28 + read_u32(&blob, 16) * 4;
+    let pool = entries + read_u32(&blob, 12) * 16;
+    let mut damaged = blob.clone();
+    damaged[entries + 16 + 8..entries + 16 + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+    fix_checksum(&mut damaged);
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::Corrupted { entry: 1 });
+    let mut damaged = blob.clone();
+    let text = pool + read_u32(&blob, entries + 8);
+    damaged[text] = 0xff;
+    fix_checksum(&mut damaged);
+    assert_eq!(CompiledCatalog::from_bytes(&damaged).unwrap_err(), Error::Corrupted { entry: 0 });
+    // The checksum fixed by the test is the one of the format.
+    let mut fixed = blob.clone();
+    fix_checksum(&mut fixed);
+    assert_eq!(fixed, blob);
+}
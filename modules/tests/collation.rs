@@ -0,0 +1,81 @@
+// The collation: the levels, the tailorings, and the packed table of the hiragana.
+
+#![cfg(feature = "std")]
+
+use std::cmp::Ordering;
+
+use modules::collation::Collator;
+
+fn sorted(tag: &str, texts: &[&str]) -> Vec<String> {
+    let mut texts: Vec<String> = texts.iter().rev().map(|text| text.to_string()).collect();
+    Collator::new(tag).sort_by_key(&mut texts, |text| text);
+    texts
+}
+
+#[test]
+fn levels() {
+    let english = Collator::new("en");
+    // Primary: the base letters; punctuation and spaces are ignored.
+    assert_eq!(english.compare("abc", "abd"), Ordering::Less);
+    assert_eq!(english.compare("b", "À"), Ordering::Greater);
+    assert_eq!(english.compare("{name}", "names"), Ordering::Less);
+    // Secondary, then tertiary, then the code points.
+    assert_eq!(sorted("en", &["resume", "résumé", "Resume", "Resumes"]), ["resume", "Resume", "résumé", "Resumes"]);
+    // Equal up to the third level: the code points ('{' is after 'n').
+    assert_eq!(english.compare("{name}", "name"), Ordering::Greater);
+    assert_eq!(english.compare("name", "name"), Ordering::Equal);
+    // Digits, Latin, other alphabets, kana, Hangul, ideographs.
+    assert_eq!(sorted("en", &["9", "z", "α", "か", "한", "日"]), ["9", "z", "α", "か", "한", "日"]);
+    // "ß" is "ss".
+    assert_eq!(sorted("en", &["st", "ß", "sr"]), ["sr", "ß", "st"]);
+}
+
+#[test]
+fn tailorings() {
+    assert_eq!(sorted("en", &["nz", "ña", "na"]), ["na", "ña", "nz"]);
+    assert_eq!(sorted("es-MX", &["nz", "ña", "na"]), ["na", "nz", "ña"]);
+    // "ー" prolongs the vowel before it: "カー" is "かあ", before "かい".
+    let japanese = Collator::new("ja");
+    assert_eq!(japanese.compare("カー", "かい"), Ordering::Less);
+    assert_eq!(japanese.compare("カー", "かあ"), Ordering::Greater);
+    assert_eq!(japanese.compare("キー", "きあ"), Ordering::Greater);
+    assert_eq!(japanese.compare("キー", "きい"), Ordering::Greater);
+    assert_eq!(japanese.compare("キー", "きう"), Ordering::Less);
+}
+
+// All the hiragana (U+3041 to U+3096), in gojūon order: small before large, unvoiced before
+// dakuten before handakuten.
+const GOJUON: &str = "ぁあぃいぅうゔぇえぉお\
+                      ゕかがきぎくぐゖけげこご\
+                      さざしじすずせぜそぞ\
+                      ただちぢっつづてでとど\
+                      なにぬねの\
+                      はばぱひびぴふぶぷへべぺほぼぽ\
+                      まみむめも\
+                      ゃやゅゆょよ\
+                      らりるれろ\
+                      ゎわゐゑを\
+                      ん";
+
+#[test]
+fn hiragana_table() {
+    let kana: Vec<String> = GOJUON.chars().map(String::from).collect();
+    let mut all: Vec<char> = ('\u{3041}'..='\u{3096}').collect();
+    all.sort();
+    let mut expected: Vec<char> = GOJUON.chars().collect();
+    expected.sort();
+    assert_eq!(all, expected);
+    let texts: Vec<&str> = kana.iter().map(String::as_str).collect();
+    for tag in ["ja", "en"] {
+        assert_eq!(sorted(tag, &texts).concat(), GOJUON);
+    }
+    // A katakana sorts as its hiragana, after it at the third level.
+    let japanese = Collator::new("ja");
+    for hiragana in GOJUON.chars() {
+        let katakana = char::from_u32(hiragana as u32 + 0x60).unwrap();
+        assert_eq!(japanese.compare(&hiragana.to_string(), &katakana.to_string()), Ordering::Less);
+        assert_eq!(japanese.compare(&format!("{}b", hiragana), &format!("{}a", katakana)), Ordering::Greater);
+    }
+    // "ヷヸヹヺ": the voiced "ワヰヱヲ".
+    assert_eq!(sorted("ja", &["わ", "ヷ", "ゐ", "ヸ", "を", "ヺ"]), ["わ", "ヷ", "ゐ", "ヸ", "を", "ヺ"]);
+}
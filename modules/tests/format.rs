@@ -0,0 +1,102 @@
+// The conventions of the languages: the calendar, the Japanese eras, the grouping of the
+// numbers and the ordinals.
+
+use modules::format::{Conventions, Date, Time};
+
+fn date(year: i32, month: u8, day: u8) -> Date {
+    Date::new(year, month, day).unwrap()
+}
+
+#[test]
+fn calendar() {
+    // Leap years: every 4 years, but not every 100 years, but every 400 years.
+    assert!(Date::new(2000, 2, 29).is_some());
+    assert!(Date::new(2024, 2, 29).is_some());
+    assert_eq!(Date::new(1900, 2, 29), None);
+    assert_eq!(Date::new(2023, 2, 29), None);
+    assert_eq!(Date::new(2022, 4, 31), None);
+    assert_eq!(Date::new(2022, 13, 1), None);
+    assert_eq!(Date::new(2022, 1, 0), None);
+    // The days since 1970-01-01, both ways.
+    assert_eq!(date(1970, 1, 1).unix_days(), 0);
+    assert_eq!(date(2000, 3, 1).unix_days(), 11_017);
+    assert_eq!(date(1969, 12, 31).unix_days(), -1);
+    for days in (-800_000..800_000).step_by(997) {
+        let date = Date::from_unix_days(days);
+        assert_eq!(Date::new(date.year(), date.month(), date.day()), Some(date));
+        assert_eq!(date.unix_days(), days);
+    }
+    // Known weekdays.
+    assert_eq!(date(1970, 1, 1).weekday(), 4);
+    assert_eq!(date(2022, 3, 21).weekday(), 1);
+    assert_eq!(date(2000, 2, 29).weekday(), 2);
+    assert_eq!(date(1900, 3, 1).weekday(), 4);
+}
+
+#[test]
+fn dates() {
+    let day = date(2022, 3, 21);
+    let full = |tag: &str| Conventions::new(tag).date(day).to_string();
+    assert_eq!(full("en"), "Monday 21 March 2022");
+    assert_eq!(full("en-US"), "Monday, March 21, 2022");
+    assert_eq!(full("ja"), "2022年3月21日月曜日");
+    assert_eq!(full("fr"), "lundi 21 mars 2022");
+    assert_eq!(full("de"), "Montag, 21. März 2022");
+    assert_eq!(full("es"), "lunes, 21 de marzo de 2022");
+    assert_eq!(full("ko"), "2022년 3월 21일 월요일");
+    assert_eq!(full("zh-Hans"), "2022年3月21日星期一");
+    let short = |tag: &str| Conventions::new(tag).short_date(date(2022, 3, 1)).to_string();
+    assert_eq!([short("en"), short("en-US"), short("ja"), short("de")], ["01/03/2022", "3/1/2022", "2022/03/01", "01.03.2022"]);
+    let time = |tag: &str, hour, minute| Conventions::new(tag).time(Time::new(hour, minute).unwrap()).to_string();
+    assert_eq!([time("en", 15, 5), time("en-US", 0, 30), time("en-US", 12, 0)], ["15:05", "12:30 AM", "12:00 PM"]);
+    assert_eq!(Time::new(24, 0), None);
+}
+
+#[test]
+fn japanese_eras() {
+    let japanese = Conventions::new("ja");
+    let era = |year, month, day| japanese.era_date(date(year, month, day)).to_string();
+    // The first year of an era is "元年".
+    assert_eq!(era(2019, 4, 30), "平成31年4月30日火曜日");
+    assert_eq!(era(2019, 5, 1), "令和元年5月1日水曜日");
+    assert_eq!(era(2022, 3, 21), "令和4年3月21日月曜日");
+    assert_eq!(era(1989, 1, 7), "昭和64年1月7日土曜日");
+    assert_eq!(era(1989, 1, 8), "平成元年1月8日日曜日");
+    // Before the Meiji era, and in the other languages: the Gregorian year.
+    assert_eq!(era(1868, 10, 22), "1868年10月22日木曜日");
+    assert_eq!(Conventions::new("en").era_date(date(2019, 5, 1)).to_string(), "Wednesday 1 May 2019");
+}
+
+#[test]
+fn grouping() {
+    let number = |tag: &str, number| Conventions::new(tag).number(number).to_string();
+    assert_eq!(number("en", 1_234_567), "1,234,567");
+    assert_eq!(number("de", 1_234_567), "1.234.567");
+    assert_eq!(number("es", 1_234_567), "1.234.567");
+    assert_eq!(number("fr", 1_234_567), "1\u{202f}234\u{202f}567");
+    assert_eq!(number("en", -1_234_567), "-1,234,567");
+    assert_eq!(number("de", -1_000), "-1.000");
+    assert_eq!(number("en", i64::MIN), "-9,223,372,036,854,775,808");
+    assert_eq!([number("en", 0), number("en", 999), number("en", 1000)], ["0", "999", "1,000"]);
+    // Spanish only groups from 5 digits.
+    assert_eq!([number("es", 1234), number("es", 12_345)], ["1234", "12.345"]);
+    let decimal = |tag: &str, number, digits| Conventions::new(tag).decimal(number, digits).to_string();
+    assert_eq!(decimal("en", 1_234_567.5, 2), "1,234,567.50");
+    assert_eq!(decimal("de", 1_234_567.5, 1), "1.234.567,5");
+    assert_eq!(decimal("fr", -1234.5678, 2), "-1\u{202f}234,57");
+    assert_eq!(decimal("en", -0.25, 1), "-0.2");
+    assert_eq!(decimal("es", 1234.5, 0), "1234");
+}
+
+#[test]
+fn ordinals() {
+    let english = |number| Conventions::new("en").ordinal(number).to_string();
+    let ordinals: Vec<String> = [1, 2, 3, 4, 11, 12, 13, 21, 22, 23, 101, 111, 112, 1013].map(english).into();
+    assert_eq!(
+        ordinals,
+        ["1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "23rd", "101st", "111th", "112th", "1,013th"]
+    );
+    let ordinal = |tag: &str, number| Conventions::new(tag).ordinal(number).to_string();
+    assert_eq!([ordinal("fr", 1), ordinal("fr", 2), ordinal("de", 21)], ["1er", "2e", "21."]);
+    assert_eq!([ordinal("es", 3), ordinal("ja", 3), ordinal("ko", 3), ordinal("zh", 3)], ["3.º", "3番目", "3번째", "第3"]);
+}
@@ -0,0 +1,74 @@
+// Furigana: the segments of the annotated phrases, and their HTML, bracket and kana forms.
+
+#![cfg(feature = "std")]
+
+use modules::catalog::{self, Formality, Phrase};
+use modules::furigana::{self, Segment};
+
+fn phrase(tag: &str, key: &str, formality: Formality) -> &'static Phrase {
+    catalog::language(tag).unwrap().phrase(key, formality).unwrap()
+}
+
+fn segments(phrase: &Phrase) -> Vec<(&str, Option<&str>)> {
+    furigana::segments(phrase).map(|Segment { base, reading }| (base, reading)).collect()
+}
+
+#[test]
+fn placeholder_before_an_annotation() {
+    // "{name}[様|さま]、ようこそいらっしゃいました。"
+    let welcome = phrase("ja", "welcome", Formality::Polite);
+    assert_eq!(
+        segments(welcome),
+        [("{name}", None), ("様", Some("さま")), ("、ようこそいらっしゃいました。", None)]
+    );
+    assert_eq!(
+        furigana::to_html(welcome),
+        "{name}<ruby>様<rp>(</rp><rt>さま</rt><rp>)</rp></ruby>、ようこそいらっしゃいました。"
+    );
+    assert_eq!(furigana::to_brackets(welcome), "{name}様[さま]、ようこそいらっしゃいました。");
+    assert_eq!(furigana::to_kana(welcome), "{name}さま、ようこそいらっしゃいました。");
+    // The placeholder still renders.
+    let mut text = String::new();
+    catalog::render(&mut text, &furigana::to_kana(welcome), &[("name", &"ユキ")]).unwrap();
+    assert_eq!(text, "ユキさま、ようこそいらっしゃいました。");
+}
+
+#[test]
+fn text_around_an_annotation() {
+    // "{name}、よう[来|き]たなあ！"
+    let welcome = phrase("ja-kansai", "welcome", Formality::Casual);
+    assert_eq!(segments(welcome), [("{name}、よう", None), ("来", Some("き")), ("たなあ！", None)]);
+    assert_eq!(furigana::to_html(welcome), "{name}、よう<ruby>来<rp>(</rp><rt>き</rt><rp>)</rp></ruby>たなあ！");
+    assert_eq!(furigana::to_brackets(welcome), "{name}、よう来[き]たなあ！");
+    assert_eq!(furigana::to_kana(welcome), "{name}、ようきたなあ！");
+}
+
+#[test]
+fn phrases_without_annotation() {
+    // Kana only: a single segment.
+    let greeting = phrase("ja", "greeting", Formality::Casual);
+    assert_eq!(greeting.ruby, None);
+    assert_eq!(segments(greeting), [("やあ！", None)]);
+    assert_eq!(furigana::to_kana(greeting), "やあ！");
+    // A "[" of a text without annotation is text, and HTML is escaped.
+    let text = Phrase {
+        text: "[a|b] & <c>",
+        ruby: None,
+        ..*greeting
+    };
+    assert_eq!(segments(&text), [("[a|b] & <c>", None)]);
+    assert_eq!(furigana::to_html(&text), "[a|b] &amp; &lt;c&gt;");
+    let empty = Phrase { text: "", ..text };
+    assert_eq!(segments(&empty), []);
+    // Consecutive annotations.
+    let annotated = Phrase {
+        ruby: Some("[日|に][本|ほん]\"<x>\""),
+        ..text
+    };
+    assert_eq!(segments(&annotated), [("日", Some("に")), ("本", Some("ほん")), ("\"<x>\"", None)]);
+    assert_eq!(
+        furigana::to_html(&annotated),
+        "<ruby>日<rp>(</rp><rt>に</rt><rp>)</rp></ruby><ruby>本<rp>(</rp><rt>ほん</rt><rp>)</rp></ruby>&quot;&lt;x&gt;&quot;"
+    );
+    assert_eq!(furigana::to_brackets(&annotated), "日[に]本[ほん]\"<x>\"");
+}
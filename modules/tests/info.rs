@@ -0,0 +1,94 @@
+// The description of the crate: its fields, its JSON form, and the languages listed by name, in
+// the English collation order.
+
+#![cfg(feature = "std")]
+
+use std::cmp::Ordering;
+
+use modules::catalog::{self, Language};
+use modules::collation::Collator;
+use modules::info::CrateInfo;
+
+#[test]
+fn languages_are_sorted_by_name() {
+    let info = modules::info();
+    let collator = Collator::new("en");
+    let names: Vec<&str> = info.sorted_languages().map(|language| language.name).collect();
+    assert_eq!(names.len(), info.languages.len());
+    assert!(names.windows(2).all(|pair| collator.compare(pair[0], pair[1]) == Ordering::Less), "{:?}", names);
+
+    // The same order in the text and in the JSON.
+    let text = info.to_string();
+    let listed: Vec<&str> = text
+        .split_once("languages:\n")
+        .unwrap()
+        .1
+        .lines()
+        .map(|line| line.trim().split_once('\t').unwrap().0)
+        .collect();
+    let tags: Vec<&str> = info.sorted_languages().map(|language| language.tag).collect();
+    assert_eq!(listed, tags);
+    let json = info.to_json();
+    let positions: Vec<usize> = tags.iter().map(|tag| json.find(&format!("{{\"tag\":\"{}\"", tag)).unwrap()).collect();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    let english = tags.iter().position(|tag| *tag == "en").unwrap();
+    assert_eq!(tags[english..english + 3], ["en", "en-GB", "en-US"]);
+}
+
+#[test]
+fn fields_describe_the_build() {
+    let info = modules::info();
+    assert_eq!((info.name, info.version), ("modules", env!("CARGO_PKG_VERSION")));
+    assert_eq!(info.catalog_version, catalog::VERSION);
+    assert_eq!(info.languages.len(), catalog::LANGUAGES.len());
+    // The features of this build, in the order of "Cargo.toml" ("all-languages" is not one).
+    let features = [
+        ("std", cfg!(feature = "std")),
+        ("metrics", cfg!(feature = "metrics")),
+        ("lang-fr", cfg!(feature = "lang-fr")),
+        ("lang-de", cfg!(feature = "lang-de")),
+        ("lang-es", cfg!(feature = "lang-es")),
+        ("lang-ko", cfg!(feature = "lang-ko")),
+        ("lang-zh-hans", cfg!(feature = "lang-zh-hans")),
+    ];
+    let expected: Vec<&str> = features.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect();
+    assert_eq!(info.features, expected);
+    let timestamp = info.build_timestamp.as_bytes();
+    assert_eq!((timestamp.len(), timestamp[4], timestamp[10], timestamp[19]), (20, b'-', b'T', b'Z'));
+    assert!(info.git_hash.is_none_or(|hash| hash.bytes().all(|byte| byte.is_ascii_hexdigit())));
+    let json = info.to_json();
+    assert!(json.starts_with(&format!("{{\"name\":\"modules\",\"version\":\"{}\",\"features\":[", info.version)));
+    assert!(json.contains(&format!("],\"catalog_version\":{},\"languages\":[", catalog::VERSION)));
+}
+
+#[test]
+fn json_shape_and_escaping() {
+    let english = catalog::language("en").unwrap();
+    let british = catalog::language("en-GB").unwrap();
+    let odd: &'static Language = Box::leak(Box::new(Language {
+        tag: "x-\"q\"",
+        name: "Back\\slash\ttab\nline\u{1}é",
+        parent: None,
+        ..*english
+    }));
+    let languages: &'static [&'static Language] = Box::leak(Box::new([british, odd, english]));
+    let info = CrateInfo {
+        name: "modules",
+        version: "1.2.3",
+        features: &["std", "lang-\"x\""],
+        catalog_version: 7,
+        languages,
+        build_timestamp: "2022-03-21T08:00:00Z",
+        git_hash: None,
+    };
+    let expected = concat!(
+        r#"{"name":"modules","version":"1.2.3","features":["std","lang-\"x\""],"catalog_version":7,"languages":["#,
+        r#"{"tag":"x-\"q\"","name":"Back\\slash\ttab\nline\u0001é","parent":null,"phrases":6},"#,
+        r#"{"tag":"en","name":"English","parent":null,"phrases":6},"#,
+        r#"{"tag":"en-GB","name":"English (United Kingdom)","parent":"en","phrases":6}"#,
+        r#"],"build_timestamp":"2022-03-21T08:00:00Z","git_hash":null}"#
+    );
+    assert_eq!(info.to_json(), expected);
+    let info = CrateInfo { languages: &[], git_hash: Some("abc123"), ..info };
+    assert!(info.to_json().ends_with(r#""languages":[],"build_timestamp":"2022-03-21T08:00:00Z","git_hash":"abc123"}"#));
+}
@@ -0,0 +1,95 @@
+// The current locale: the overrides restore the previous locale, whether they end normally or
+// by a panic, and a scoped future keeps its locale from one thread to another.
+
+#![cfg(feature = "std")]
+
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+
+use modules::catalog::Formality;
+use modules::locale;
+
+fn current() -> &'static str {
+    locale::current().tag
+}
+
+#[test]
+fn nested_overrides_are_restored() {
+    assert_eq!(current(), "en");
+    let tags = locale::with_locale("ja", || {
+        let inner = locale::with_locale("en-GB", || {
+            let innermost = locale::with_locale("ja-kansai", current);
+            (current(), innermost)
+        });
+        (current(), inner)
+    });
+    assert_eq!(tags, ("ja", ("en-GB", "ja-kansai")));
+    assert_eq!(current(), "en");
+    // A change inside an override ends with it.
+    locale::with_locale("ja", || locale::set_current("en-US"));
+    assert_eq!(current(), "en");
+    // The lookups follow the current locale.
+    let greeting = locale::greeting(Formality::Polite);
+    assert_ne!(locale::with_locale("ja", || locale::greeting(Formality::Polite)), greeting);
+}
+
+#[test]
+fn panics_restore_the_locale() {
+    locale::set_current("en-GB");
+    let result = panic::catch_unwind(|| {
+        locale::with_locale("ja", || {
+            locale::with_locale("ja-kansai", || panic!("in the scope"));
+        })
+    });
+    assert!(result.is_err());
+    assert_eq!(current(), "en-GB");
+    locale::set_current("en");
+}
+
+// A future that is pending the first time it is polled, and returns the current locale the
+// second time.
+struct YieldOnce(bool);
+
+impl Future for YieldOnce {
+    type Output = &'static str;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<&'static str> {
+        if self.0 {
+            return Poll::Ready(current());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+struct NoWake;
+
+impl Wake for NoWake {
+    fn wake(self: Arc<Self>) {}
+}
+
+#[test]
+fn scoped_future_follows_the_task() {
+    let waker = Waker::from(Arc::new(NoWake));
+    let mut future = Box::pin(locale::scope("ja", YieldOnce(false)));
+    let mut cx = Context::from_waker(&waker);
+    assert!(future.as_mut().poll(&mut cx).is_pending());
+    assert_eq!(current(), "en");
+    // Polled again by another thread, whose locale is another one.
+    let (inside, outside) = thread::spawn(move || {
+        locale::set_current("en-US");
+        let waker = Waker::from(Arc::new(NoWake));
+        let inside = future.as_mut().poll(&mut Context::from_waker(&waker));
+        (inside, current())
+    })
+    .join()
+    .unwrap();
+    assert_eq!(inside, Poll::Ready("ja"));
+    assert_eq!(outside, "en-US");
+    assert_eq!(current(), "en");
+}
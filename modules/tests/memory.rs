@@ -0,0 +1,62 @@
+// The completeness check and the translation memory: the phrases that a language lacks, and the
+// suggestions for them.
+
+#![cfg(feature = "std")]
+
+use modules::catalog::{self, Formality, Key, Language, LANGUAGES};
+use modules::memory::TranslationMemory;
+use modules::po;
+
+// Japanese without the phrases "welcome.*".
+fn partial_japanese() -> &'static Language {
+    let japanese = catalog::language("ja").unwrap();
+    let phrases = &japanese.phrases[..4];
+    assert!(phrases.iter().all(|phrase| phrase.key != "welcome"));
+    Box::leak(Box::new(Language { phrases, ..*japanese }))
+}
+
+#[test]
+fn missing_phrases_are_listed() {
+    let japanese = partial_japanese();
+    let missing: Vec<String> = catalog::missing(japanese)
+        .map(|phrase| format!("{}.{}", phrase.key, phrase.formality.as_str()))
+        .collect();
+    assert_eq!(missing, ["welcome.casual", "welcome.polite"]);
+    // A regional variant inherits the phrases of its base language: Kansai defines
+    // "welcome.casual".
+    let kansai = Box::leak(Box::new(Language {
+        parent: Some(japanese),
+        ..*catalog::language("ja-kansai").unwrap()
+    }));
+    let missing: Vec<&str> = catalog::missing(kansai).map(|phrase| phrase.text).collect();
+    assert_eq!(missing, ["Welcome, {name}. We are glad to see you."]);
+    // The catalog is complete.
+    for language in LANGUAGES {
+        assert_eq!(catalog::missing(language).count(), 0, "{}", language.tag);
+    }
+}
+
+#[test]
+fn missing_phrases_fall_back_to_the_source_language() {
+    let japanese = partial_japanese();
+    assert_eq!(japanese.phrase("welcome", Formality::Casual), None);
+    assert_eq!(japanese.get(Key::Welcome, Formality::Casual).text, "Welcome, {name}!");
+    assert_eq!(japanese.get(Key::Greeting, Formality::Casual).text, "やあ！");
+}
+
+#[test]
+fn missing_phrases_get_suggestions() {
+    let japanese = partial_japanese();
+    let mut memory = TranslationMemory::from_catalog(japanese);
+    assert_eq!(memory.len(), 4);
+    let missing: Vec<&str> = catalog::missing(japanese).map(|phrase| phrase.text).collect();
+    assert!(memory.suggest(missing[0], 3).is_empty());
+    // The PO file of the complete language gives them.
+    let file = po::parse(&po::export(catalog::language("ja").unwrap())).unwrap();
+    memory.import_po(&file);
+    let suggestions = memory.suggest(missing[0], 3);
+    assert_eq!((suggestions[0].score, suggestions[0].unit.translation.as_str()), (100, "{name}、いらっしゃい！"));
+    let suggestions = memory.suggest("Welcome, {name}. Glad to see you.", 3);
+    assert_eq!(suggestions[0].unit.translation, "{name}様、ようこそいらっしゃいました。");
+    assert!(suggestions[0].score < 100);
+}
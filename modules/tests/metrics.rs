@@ -0,0 +1,53 @@
+// The usage counters: a known sequence of lookups, the snapshot and the Prometheus text format.
+
+#![cfg(feature = "metrics")]
+
+use modules::catalog::{self, Formality, Key};
+use modules::{locale, metrics, review};
+
+#[test]
+fn lookups_are_counted() {
+    metrics::reset();
+    assert_eq!(metrics::snapshot().total(), 0);
+
+    // "en-GB" inherits "farewell.polite" from "en": a fallback.
+    assert_eq!(catalog::lookup("en-GB", "farewell", Formality::Polite), Some("Goodbye."));
+    assert_eq!(catalog::lookup("en-gb", "farewell", Formality::Polite), Some("Goodbye."));
+    assert_eq!(catalog::lookup("ja", "greeting", Formality::Casual), Some("やあ！"));
+    locale::with_locale("ja", || assert_eq!(locale::greeting(Formality::Polite), "おはようございます。"));
+    // The Kansai "greeting.polite" is a draft: the Japanese one is served.
+    assert_eq!(review::lookup_approved("ja-kansai", "greeting", Formality::Polite), Some("おはようございます。"));
+    // Not counted: unknown phrases, and the phrases read directly.
+    assert_eq!(catalog::lookup("ja", "unknown", Formality::Casual), None);
+    assert_eq!(modules::phrase!(ja, greeting.casual), "やあ！");
+    assert_eq!(catalog::language("ja").unwrap().get(Key::Greeting, Formality::Casual).text, "やあ！");
+
+    let snapshot = metrics::snapshot();
+    assert_eq!(snapshot.total(), 5);
+    let samples: Vec<_> = snapshot
+        .samples()
+        .map(|sample| (sample.language, sample.key, sample.formality, sample.fallback, sample.count))
+        .collect();
+    assert_eq!(
+        samples,
+        [
+            ("ja", "greeting", Formality::Casual, false, 1),
+            ("ja", "greeting", Formality::Polite, false, 1),
+            ("en-GB", "farewell", Formality::Polite, true, 2),
+            ("ja-kansai", "greeting", Formality::Polite, true, 1),
+        ]
+    );
+    let expected = r#"# HELP modules_phrase_lookups_total Number of phrase lookups served by the catalog.
+# TYPE modules_phrase_lookups_total counter
+modules_phrase_lookups_total{language="ja",key="greeting",formality="casual",fallback="false"} 1
+modules_phrase_lookups_total{language="ja",key="greeting",formality="polite",fallback="false"} 1
+modules_phrase_lookups_total{language="en-GB",key="farewell",formality="polite",fallback="true"} 2
+modules_phrase_lookups_total{language="ja-kansai",key="greeting",formality="polite",fallback="true"} 1
+"#;
+    assert_eq!(snapshot.to_string(), expected);
+
+    // The snapshot is a copy.
+    metrics::reset();
+    assert_eq!(snapshot.total(), 5);
+    assert_eq!(metrics::snapshot().samples().count(), 0);
+}
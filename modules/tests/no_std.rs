@@ -0,0 +1,19 @@
+// Check that the library builds without the standard library.
+
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn library_builds_without_std() {
+    let manifest_dir = env!("CARGO_MANIFEST_DIR");
+    // A separate target directory, so that this build does not wait for the lock held by the
+    // "cargo test" that runs it.
+    let target_dir = Path::new(manifest_dir).join("target").join("no_std");
+    let status = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--no-default-features", "--target-dir"])
+        .arg(&target_dir)
+        .current_dir(manifest_dir)
+        .status()
+        .expect("cannot run cargo");
+    assert!(status.success(), "the library does not build with \"--no-default-features\"");
+}
@@ -0,0 +1,72 @@
+// The language packs: reachable with their feature, absent without it. Run the tests with
+// "--features all-languages" too.
+
+use modules::catalog::{self, Formality, LANGUAGES, LANGUAGE_COUNT};
+
+// The packs: tag, feature enabled, "greeting.casual".
+const PACKS: [(&str, bool, &str); 5] = [
+    ("fr", cfg!(feature = "lang-fr"), "Salut !"),
+    ("de", cfg!(feature = "lang-de"), "Hallo!"),
+    ("es", cfg!(feature = "lang-es"), "¡Hola!"),
+    ("ko", cfg!(feature = "lang-ko"), "안녕!"),
+    ("zh-Hans", cfg!(feature = "lang-zh-hans"), "嗨！"),
+];
+
+#[test]
+fn packs_follow_their_feature() {
+    for (tag, enabled, greeting) in PACKS {
+        let upper = tag.to_ascii_uppercase();
+        if enabled {
+            let language = catalog::language(&upper).unwrap();
+            assert_eq!(language.tag, tag);
+            assert!(LANGUAGES.iter().any(|other| core::ptr::eq(*other, language)));
+            assert_eq!(catalog::lookup(tag, "greeting", Formality::Casual), Some(greeting));
+            assert_eq!(catalog::missing(language).count(), 0, "{}", tag);
+            assert_eq!(catalog::resolve(&format!("{}-XX", tag)).tag, tag);
+        } else {
+            // The lookups fail, and the region falls back to English.
+            assert!(catalog::language(&upper).is_none(), "{}", tag);
+            assert_eq!(catalog::lookup(tag, "greeting", Formality::Casual), None);
+            assert_eq!(catalog::resolve(&format!("{}-XX", tag)).tag, "en");
+        }
+    }
+    let enabled = PACKS.iter().filter(|(_, enabled, _)| *enabled).count();
+    assert_eq!(LANGUAGE_COUNT, 5 + enabled);
+    assert_eq!(LANGUAGES.len(), LANGUAGE_COUNT);
+}
+
+#[cfg(feature = "lang-fr")]
+#[test]
+fn french() {
+    assert_eq!(modules::phrase!(fr, greeting.polite), "Bonjour.");
+    assert_eq!(modules::french::welcome::text(Formality::Casual), "Bienvenue, {name} !");
+    assert_eq!(modules::french::LANGUAGE.get(catalog::Key::Farewell, Formality::Polite).text, "Au revoir.");
+}
+
+#[cfg(feature = "lang-de")]
+#[test]
+fn german() {
+    assert_eq!(modules::phrase!(de, greeting.polite), "Guten Morgen.");
+    assert_eq!(modules::german::farewells::POLITE, "Auf Wiedersehen.");
+}
+
+#[cfg(feature = "lang-es")]
+#[test]
+fn spanish() {
+    assert_eq!(modules::phrase!(es, farewell.polite), "Adiós.");
+    assert_eq!(modules::spanish::welcome::CASUAL, "¡Bienvenido, {name}!");
+}
+
+#[cfg(feature = "lang-ko")]
+#[test]
+fn korean() {
+    assert_eq!(modules::phrase!(ko, farewell.polite), "안녕히 가세요.");
+    assert_eq!(modules::korean::welcome::CASUAL, "{name}, 어서 와!");
+}
+
+#[cfg(feature = "lang-zh-hans")]
+#[test]
+fn simplified_chinese() {
+    assert_eq!(modules::phrase!(zh_hans, farewell.polite), "再见。");
+    assert_eq!(modules::simplified_chinese::welcome::CASUAL, "{name}，欢迎！");
+}
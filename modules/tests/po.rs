@@ -0,0 +1,104 @@
+// The PO files: export and parsing, the fuzzy entries, and the errors.
+
+#![cfg(feature = "std")]
+
+use modules::catalog::{self, LANGUAGES};
+use modules::memory::TranslationMemory;
+use modules::po::{self, Entry};
+
+fn entry(context: &str, source: &str, translation: &str, fuzzy: bool) -> Entry {
+    Entry {
+        context: Some(context.to_string()),
+        source: source.to_string(),
+        translation: translation.to_string(),
+        fuzzy,
+    }
+}
+
+#[test]
+fn exported_files_are_parsed_back() {
+    let japanese = catalog::language("ja").unwrap();
+    let file = po::parse(&po::export(japanese)).unwrap();
+    assert_eq!(file.language.as_deref(), Some("ja"));
+    assert_eq!(file.entries.len(), LANGUAGES[0].phrases.len());
+    for (entry, phrase) in file.entries.iter().zip(LANGUAGES[0].phrases) {
+        let id = format!("{}.{}", phrase.key, phrase.formality.as_str());
+        assert_eq!(entry.context.as_deref(), Some(id.as_str()));
+        assert_eq!(entry.source, phrase.text);
+        let translation = japanese.phrase(phrase.key, phrase.formality).map_or("", |phrase| phrase.text);
+        assert_eq!(entry.translation, translation);
+        assert!(!entry.fuzzy);
+    }
+}
+
+#[test]
+fn strings_are_unquoted_and_joined() {
+    let text = r#"
+msgid ""
+msgstr ""
+"Project-Id-Version: 1\n"
+"Language: fr\n"
+
+# A translator comment.
+msgctxt "note.casual"
+msgid "Say \"hi\"\t"
+"to\\them\n"
+msgstr "Dis \"salut\"\t"
+"à\\eux\n"
+"#;
+    let file = po::parse(text).unwrap();
+    assert_eq!(file.language.as_deref(), Some("fr"));
+    assert_eq!(file.entries, [entry("note.casual", "Say \"hi\"\tto\\them\n", "Dis \"salut\"\tà\\eux\n", false)]);
+}
+
+#[test]
+fn fuzzy_entries_are_marked_and_not_imported() {
+    let text = r#"
+#, fuzzy
+msgctxt "greeting.casual"
+msgid "Hi!"
+msgstr "Salut !"
+
+#: src/main.rs:12
+#, c-format, fuzzy
+msgctxt "farewell.casual"
+msgid "Bye!"
+msgstr "Ciao !"
+
+#, c-format
+msgctxt "greeting.polite"
+msgid "Good morning."
+msgstr "Bonjour."
+"#;
+    let file = po::parse(text).unwrap();
+    assert_eq!(
+        file.entries,
+        [
+            entry("greeting.casual", "Hi!", "Salut !", true),
+            entry("farewell.casual", "Bye!", "Ciao !", true),
+            entry("greeting.polite", "Good morning.", "Bonjour.", false),
+        ]
+    );
+    let mut memory = TranslationMemory::new();
+    assert_eq!(memory.import_po(&file), 1);
+    assert_eq!(memory.len(), 1);
+}
+
+#[test]
+fn invalid_files_are_rejected() {
+    let error = |text: &str| po::parse(text).unwrap_err();
+    let plural = "msgid \"a file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"un fichier\"\n";
+    assert_eq!(error(plural).line, 2);
+    assert!(error(plural).message.contains("plural"), "{}", error(plural));
+    let plural = "msgid \"a file\"\nmsgstr[0] \"un fichier\"\n";
+    assert_eq!(error(plural).to_string(), "line 2: plural forms are not supported (\"msgstr[0]\")");
+    assert_eq!(error("msgstr \"x\"").line, 1);
+    assert_eq!(error("msgid \"x\"\nmsgstr \"y\"\nmsgstr \"z\"").line, 3);
+    assert_eq!(error("msgid \"x\"\n").message, "\"msgid\" without \"msgstr\"");
+    assert_eq!(error("msgctxt \"x\"\nmsgctxt \"y\"").message, "\"msgctxt\" without \"msgid\"");
+    assert_eq!(error("\"orphan\"").message, "string outside of an entry");
+    assert_eq!(error("msgid \"\\q\"").message, "unknown escape sequence \"\\q\"");
+    assert_eq!(error("msgid \"a\"b\"").message, "unescaped quote inside a string");
+    assert_eq!(error("msgid x").message, "expected a quoted string, found x");
+    assert_eq!(error("msgid \"x\"\nmsgstr \"y\"\nobsolete \"z\"").message, "unsupported keyword \"obsolete\"");
+}
@@ -0,0 +1,78 @@
+// The review workflow: the approved lookups, and the stale translations.
+
+use modules::catalog::{self, Formality, Language, Phrase, LANGUAGES};
+use modules::review::{self, source_hash, Review, Status};
+
+fn phrase(tag: &str, key: &str, formality: Formality) -> &'static Phrase {
+    catalog::language(tag).unwrap().phrase(key, formality).unwrap()
+}
+
+#[test]
+fn source_hash_is_fnv_1a() {
+    assert_eq!(source_hash(""), 0x811c_9dc5);
+    assert_eq!(source_hash("a"), 0xe40c_292c);
+    assert_eq!(source_hash("Hi!"), 0x325d_0001);
+    assert_eq!(source_hash("Good morning."), 0x575b_1d3a);
+    const HASH: u32 = source_hash("Hi!");
+    assert_eq!(HASH, 0x325d_0001);
+}
+
+#[test]
+fn statuses() {
+    let casual = phrase("ja-kansai", "welcome", Formality::Casual);
+    assert_eq!(casual.review.unwrap().status, Status::Reviewed);
+    assert!(!casual.is_approved());
+    let polite = phrase("ja-kansai", "greeting", Formality::Polite);
+    assert_eq!(
+        polite.review,
+        Some(Review {
+            status: Status::Draft,
+            translator: "Shota Nishimura",
+            timestamp: "2022-04-02",
+            source_hash: 0x575b_1d3a,
+        })
+    );
+    assert!(!polite.is_approved());
+    assert!(phrase("ja", "greeting", Formality::Polite).is_approved());
+    // The source language is the reference: approved, never stale.
+    let source = phrase("en", "greeting", Formality::Polite);
+    assert_eq!(source.review, None);
+    assert!(source.is_approved() && !source.is_stale());
+    assert!(Status::Draft < Status::Translated && Status::Reviewed < Status::Approved);
+}
+
+#[test]
+fn lookup_skips_the_unapproved_translations() {
+    // The Kansai "greeting.polite" is a draft: the Japanese one is served.
+    assert_eq!(review::lookup_approved("ja-kansai", "greeting", Formality::Polite), Some("おはようございます。"));
+    assert_eq!(catalog::lookup("ja-kansai", "greeting", Formality::Polite), Some("おはようさん。"));
+    // "welcome.casual" is only reviewed.
+    assert_eq!(review::lookup_approved("ja-kansai", "welcome", Formality::Casual), Some("{name}、いらっしゃい！"));
+    assert_eq!(review::lookup_approved("ja-kansai", "greeting", Formality::Casual), Some("まいど！"));
+    assert_eq!(review::lookup_approved("en", "greeting", Formality::Casual), Some("Hi!"));
+    assert_eq!(review::lookup_approved("ja", "unknown", Formality::Casual), None);
+    assert_eq!(review::lookup_approved("xx", "greeting", Formality::Casual), None);
+}
+
+#[test]
+fn stale_translations() {
+    // "build.rs" warns about the stale translations: there is none in the catalog.
+    for language in LANGUAGES {
+        assert_eq!(review::stale(language).count(), 0, "{}", language.tag);
+    }
+    // A translation of a former source text.
+    let japanese = catalog::language("ja").unwrap();
+    let mut phrases = japanese.phrases.to_vec();
+    let review = phrases[0].review.unwrap();
+    assert_eq!(review.source_hash, source_hash("Hi!"));
+    phrases[0].review = Some(Review {
+        source_hash: source_hash("Hello!"),
+        ..review
+    });
+    let phrases: &'static [Phrase] = phrases.leak();
+    assert!(phrases[0].is_stale());
+    assert!(!phrases[1].is_stale());
+    let language: &'static Language = Box::leak(Box::new(Language { phrases, ..*japanese }));
+    let stale: Vec<&str> = review::stale(language).map(|phrase| phrase.text).collect();
+    assert_eq!(stale, ["やあ！"]);
+}
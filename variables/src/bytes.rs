@@ -0,0 +1,152 @@
+// The linearize codec on bytes ("&[u8]") and on the strings of the system ("OsStr": file names,
+// command-line arguments, environment variables), which may not be UTF-8.
+//
+// The valid UTF-8 parts are escaped by "codec::escape()". Each byte of the invalid parts is
+// written "\xHH":
+//
+//     bytes                      escaped
+//     b"caf\xc3\xa9\n"           café\n
+//     b"caf\xe9\n"               caf\xe9\n
+//
+// Round-trip guarantee:
+//
+// - For every "bytes", "unescape(&escape(bytes)) == Ok(bytes)": the exact bytes are restored.
+// - For valid UTF-8, the output is the one of "codec::escape()": "escape(text.as_bytes()) ==
+//   codec::escape(text)". Thus "unescape()" also reads the output of "codec::escape()".
+//
+// "unescape()" accepts "\xHH" for any byte, even the ones that would be valid UTF-8 ("\x41" is
+// "A"); "codec::unescape()" does not accept "\x" at all. As "codec::unescape()", it rejects a raw
+// '\n' or '\r'.
+
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+use std::fmt::Write as _;
+
+use crate::codec::{self, Error, ErrorKind, Position};
+
+pub fn escape(bytes: &[u8]) -> Cow<'_, str> {
+    let mut chunks = bytes.utf8_chunks();
+    let Some(first) = chunks.next() else {
+        return Cow::Borrowed("");
+    };
+    // Valid UTF-8: no copy if there is nothing to escape.
+    if first.invalid().is_empty() {
+        return codec::escape(first.valid());
+    }
+    let mut result = String::with_capacity(bytes.len() + 3 * first.invalid().len());
+    for chunk in std::iter::once(first).chain(chunks) {
+        result.push_str(&codec::escape(chunk.valid()));
+        for byte in chunk.invalid() {
+            write!(result, "\\x{:02x}", byte).expect("writing into a String does not fail");
+        }
+    }
+    Cow::Owned(result)
+}
+
+// Restore the bytes written by "escape()". The errors are those of "codec::unescape()", plus
+// the ones of "\x" ("InvalidHexEscape").
+pub fn unescape(text: &str) -> Result<Vec<u8>, Error> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut position = Position::default();
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        let (before, sequence) = rest.split_at(index);
+        copy(&mut result, before, &mut position)?;
+        let (byte, length) = decode(sequence).map_err(|kind| position.error(kind))?;
+        result.push(byte);
+        // The escape sequences are ASCII, on a single line.
+        position.byte_offset += length;
+        position.char_offset += length;
+        position.column += length;
+        rest = &sequence[length..];
+    }
+    copy(&mut result, rest, &mut position)?;
+    Ok(result)
+}
+
+// Append "text" (without escape sequence) to "result", and advance "position" after it.
+fn copy(result: &mut Vec<u8>, text: &str, position: &mut Position) -> Result<(), Error> {
+    for c in text.chars() {
+        if c == '\n' || c == '\r' {
+            return Err(position.error(ErrorKind::RawLineBreak(c)));
+        }
+        position.advance(c);
+    }
+    result.extend_from_slice(text.as_bytes());
+    Ok(())
+}
+
+// Decode the escape sequence at the start of "sequence" (a backslash): the byte, and the length
+// of the sequence.
+fn decode(sequence: &str) -> Result<(u8, usize), ErrorKind> {
+    match sequence[1..].chars().next().ok_or(ErrorKind::DanglingBackslash)? {
+        'n' => Ok((b'\n', 2)),
+        'r' => Ok((b'\r', 2)),
+        '\\' => Ok((b'\\', 2)),
+        'x' => {
+            let (value, length) = codec::decode_hex(&sequence[2..], 0xff)?;
+            Ok((value as u8, 2 + length))
+        }
+        c => Err(ErrorKind::UnknownEscape(c)),
+    }
+}
+
+// Escape a string of the system. Its bytes are the ones of "OsStr::as_encoded_bytes()": on
+// Unix, the bytes given to the system; on Windows, UTF-8, extended to the unpaired surrogates
+// (escaped, as they are not valid UTF-8).
+pub fn escape_os_str(text: &OsStr) -> Cow<'_, str> {
+    escape(text.as_encoded_bytes())
+}
+
+// Restore a string written by "escape_os_str()", exactly.
+//
+// On Unix, any bytes are valid. On Windows, the bytes must be the ones of a string of the system:
+// UTF-8, extended to the unpaired surrogates (the unpaired surrogates of the original string are
+// thus restored). Elsewhere, the bytes must be valid UTF-8. Otherwise, the error is
+// "InvalidUtf8", at the start of the text.
+pub fn unescape_os_str(text: &str) -> Result<OsString, Error> {
+    let bytes = unescape(text)?;
+    #[cfg(unix)]
+    return Ok(std::os::unix::ffi::OsStringExt::from_vec(bytes));
+    #[cfg(windows)]
+    return match is_wtf8(&bytes) {
+        // SAFETY: the bytes are UTF-8 extended to the unpaired surrogates (WTF-8), the encoding
+        // of "OsStr::as_encoded_bytes()" on Windows.
+        true => Ok(unsafe { OsStr::from_encoded_bytes_unchecked(&bytes) }.to_os_string()),
+        false => Err(Position::default().error(ErrorKind::InvalidUtf8)),
+    };
+    #[cfg(not(any(unix, windows)))]
+    return String::from_utf8(bytes)
+        .map(OsString::from)
+        .map_err(|_| Position::default().error(ErrorKind::InvalidUtf8));
+}
+
+// Whether "bytes" is UTF-8 extended to the unpaired surrogates (WTF-8): a surrogate is written as
+// a code point of 3 bytes ("\xed\xa0\x80" for U+D800), and a high surrogate must not be
+// followed by a low one (the pair is a single code point, written as such).
+#[cfg(windows)]
+fn is_wtf8(bytes: &[u8]) -> bool {
+    let mut rest = bytes;
+    // Whether the last code point is a high surrogate.
+    let mut high = false;
+    loop {
+        let error = match std::str::from_utf8(rest) {
+            Ok(_) => return true,
+            Err(error) => error,
+        };
+        if error.valid_up_to() > 0 {
+            high = false;
+        }
+        match rest[error.valid_up_to()..] {
+            [0xed, second @ 0xa0..=0xbf, 0x80..=0xbf, ..] => {
+                let is_high = second < 0xb0;
+                if high && !is_high {
+                    return false;
+                }
+                high = is_high;
+                rest = &rest[error.valid_up_to() + 3..];
+            }
+            _ => return false,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+// The "linearize" escape codec, and the escape sequences of Rust literals.
+//
+// "escape()" writes a text on a single line: the line feeds, carriage returns and backslashes are
+// replaced by escape sequences. "unescape()" restores the original text.
+//
+//     character    escape sequence
+//     '\n'         \n
+//     '\r'         \r
+//     '\\'         \\
+//
+// Round-trip guarantee:
+//
+// - For every string "text", "unescape(&escape(text)) == Ok(text)", and "escape(text)" contains
+//   neither '\n' nor '\r'.
+// - Each character has a single escaped form, and "unescape()" only accepts these forms. Thus, for
+//   every string "escaped" that "unescape()" accepts, "escape(&unescape(escaped)?) == escaped".
+//
+// Any other use of the backslash in an escaped text is an error, reported with its position, as
+// well as a raw '\n' or '\r' (it would be written "\n" or "\r").
+//
+// "escape_with()" and "unescape_with()" also offer the escape sequences of the Rust string
+// literals ("Style::Default" and "Style::Debug"): \t, \0, \', \", \xHH (up to \x7f) and \u{...}
+// (1 to 6 hexadecimal digits, "_" allowed after the first one, surrogates excluded). The output
+// is the one of "str::escape_default()" and "str::escape_debug()", byte for byte. The first
+// guarantee holds for these styles too, but not the second one: "\x41" and "A" are both "A".
+
+use std::borrow::Cow;
+use std::fmt;
+
+// The escape sequences used by "escape_with()" and accepted by "unescape_with()".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    // \n, \r and \\ only, as "escape()" and "unescape()".
+    Linearize,
+    // Same output as "str::escape_default()": everything but the printable ASCII characters is
+    // escaped, the non-ASCII characters as \u{...}.
+    Default,
+    // Same output as "str::escape_debug()": the control characters, the quotes and the characters
+    // that are not printable are escaped. The other non-ASCII characters are kept.
+    Debug,
+}
+
+// Replace the line feeds, carriage returns and backslashes by escape sequences. A text without
+// them is returned as it is, without allocation.
+//
+// The escaped characters are ASCII, thus the text is scanned as bytes, 8 at a time (see
+// "find_escaped()"), and the parts between them are copied at once. The result is allocated
+// once, with its final size: each escaped character takes one more byte.
+pub fn escape(text: &str) -> Cow<'_, str> {
+    let bytes = text.as_bytes();
+    let Some(first) = find_escaped(bytes) else {
+        return Cow::Borrowed(text);
+    };
+    let mut count = 1;
+    let mut index = first + 1;
+    while let Some(next) = find_escaped(&bytes[index..]) {
+        count += 1;
+        index += next + 1;
+    }
+    let mut result = String::with_capacity(text.len() + count);
+    let mut rest = text;
+    let mut next = Some(first);
+    while let Some(index) = next {
+        result.push_str(&rest[..index]);
+        result.push_str(match rest.as_bytes()[index] {
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            _ => "\\\\",
+        });
+        rest = &rest[index + 1..];
+        next = find_escaped(rest.as_bytes());
+    }
+    result.push_str(rest);
+    Cow::Owned(result)
+}
+
+// The index of the first '\n', '\r' or '\\' of "bytes".
+//
+// The bytes are read 8 at a time, as a "u64": "x.wrapping_sub(LOW) & !x & HIGH" is not 0 if one
+// of the bytes of "x" is 0, and "word ^ (LOW * byte)" turns the bytes equal to "byte" into 0.
+pub(crate) fn find_escaped(bytes: &[u8]) -> Option<usize> {
+    const LOW: u64 = 0x0101_0101_0101_0101;
+    const HIGH: u64 = 0x8080_8080_8080_8080;
+    let contains = |word: u64, byte: u8| {
+        let x = word ^ (LOW * byte as u64);
+        x.wrapping_sub(LOW) & !x & HIGH != 0
+    };
+    let mut offset = 0;
+    for chunk in bytes.chunks_exact(8) {
+        let word = u64::from_ne_bytes(chunk.try_into().expect("chunks of 8 bytes"));
+        if contains(word, b'\n') || contains(word, b'\r') || contains(word, b'\\') {
+            break;
+        }
+        offset += 8;
+    }
+    bytes[offset..]
+        .iter()
+        .position(|byte| matches!(byte, b'\n' | b'\r' | b'\\'))
+        .map(|index| offset + index)
+}
+
+// Restore a text written by "escape()".
+pub fn unescape(text: &str) -> Result<String, Error> {
+    unescape_with(text, Style::Linearize)
+}
+
+pub fn escape_with(text: &str, style: Style) -> Cow<'_, str> {
+    match style {
+        Style::Linearize => escape(text),
+        Style::Default => Cow::Owned(text.escape_default().to_string()),
+        Style::Debug => Cow::Owned(text.escape_debug().to_string()),
+    }
+}
+
+// Restore a text written by "escape_with()". "Style::Default" and "Style::Debug" accept all the
+// escape sequences of the Rust string literals (but the line continuation).
+pub fn unescape_with(text: &str, style: Style) -> Result<String, Error> {
+    let mut result = String::with_capacity(text.len());
+    let mut position = Position::default();
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        let (before, sequence) = rest.split_at(index);
+        copy(&mut result, before, &mut position, style)?;
+        let (c, length) = decode(sequence, style).map_err(|kind| position.error(kind))?;
+        result.push(c);
+        // The escape sequences are ASCII, on a single line.
+        position.byte_offset += length;
+        position.char_offset += length;
+        position.column += length;
+        rest = &sequence[length..];
+    }
+    copy(&mut result, rest, &mut position, style)?;
+    Ok(result)
+}
+
+// Copy a text without escape sequence into "result". For "Style::Linearize", the line breaks
+// must be escaped: the raw ones are errors.
+fn copy(result: &mut String, text: &str, position: &mut Position, style: Style) -> Result<(), Error> {
+    if style == Style::Linearize {
+        if let Some(index) = text.find(['\n', '\r']) {
+            text[..index].chars().for_each(|c| position.advance(c));
+            let c = text[index..].chars().next().expect("found above");
+            return Err(position.error(ErrorKind::RawLineBreak(c)));
+        }
+    }
+    result.push_str(text);
+    text.chars().for_each(|c| position.advance(c));
+    Ok(())
+}
+
+// Decode the escape sequence at the start of "sequence" (a backslash): the character, and the
+// length of the sequence.
+pub(crate) fn decode(sequence: &str, style: Style) -> Result<(char, usize), ErrorKind> {
+    let c = sequence[1..].chars().next().ok_or(ErrorKind::DanglingBackslash)?;
+    let decoded = match (c, style) {
+        ('n', _) => '\n',
+        ('r', _) => '\r',
+        ('\\', _) => '\\',
+        (c, Style::Linearize) => return Err(ErrorKind::UnknownEscape(c)),
+        ('t', _) => '\t',
+        ('0', _) => '\0',
+        ('\'', _) => '\'',
+        ('"', _) => '"',
+        ('x', _) => {
+            let (c, length) = decode_hex(&sequence[2..], 0x7f)?;
+            return Ok((c, 2 + length));
+        }
+        ('u', _) => {
+            let (c, length) = decode_unicode(&sequence[2..])?;
+            return Ok((c, 2 + length));
+        }
+        (c, _) => return Err(ErrorKind::UnknownEscape(c)),
+    };
+    Ok((decoded, 2))
+}
+
+// Decode the two hexadecimal digits after "\x" (at most "max"): the character, and the length of
+// the digits.
+pub(crate) fn decode_hex(digits: &str, max: u8) -> Result<(char, usize), ErrorKind> {
+    let digits = digits
+        .get(..2)
+        .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+        .ok_or(ErrorKind::InvalidHexEscape)?;
+    let value = u8::from_str_radix(digits, 16).expect("checked above");
+    if value > max {
+        return Err(ErrorKind::HexEscapeOutOfRange(value));
+    }
+    Ok((value as char, 2))
+}
+
+// Decode the "{...}" after "\u": the character, and the length of the braces and digits.
+pub(crate) fn decode_unicode(braces: &str) -> Result<(char, usize), ErrorKind> {
+    let body = braces.strip_prefix('{').ok_or(ErrorKind::InvalidUnicodeEscape)?;
+    let end = body.find('}').ok_or(ErrorKind::InvalidUnicodeEscape)?;
+    let digits = &body[..end];
+    let valid = !digits.starts_with('_')
+        && digits.bytes().all(|byte| byte.is_ascii_hexdigit() || byte == b'_')
+        && (1..=6).contains(&digits.bytes().filter(|byte| *byte != b'_').count());
+    if !valid {
+        return Err(ErrorKind::InvalidUnicodeEscape);
+    }
+    let value = digits
+        .bytes()
+        .filter(|byte| *byte != b'_')
+        .fold(0, |value, digit| value * 16 + (digit as char).to_digit(16).expect("checked above"));
+    let c = char::from_u32(value).ok_or(ErrorKind::InvalidCodePoint(value))?;
+    Ok((c, end + 2))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    // The text ends with a backslash (with the escape character, for an "EscapeScheme").
+    DanglingBackslash,
+    // A backslash followed by a character that does not form an escape sequence ("\t" for
+    // "Style::Linearize").
+    UnknownEscape(char),
+    // A '\n' or '\r' that is not escaped ("Style::Linearize").
+    RawLineBreak(char),
+    // "\x" not followed by two hexadecimal digits.
+    InvalidHexEscape,
+    // "\x" above "\x7f": only ASCII characters can be written this way (in Rust literals).
+    HexEscapeOutOfRange(u8),
+    // "\u" not followed by 1 to 6 hexadecimal digits between braces.
+    InvalidUnicodeEscape,
+    // "\u{...}" of a surrogate, or above "\u{10ffff}". For "Fallback::Utf16", a surrogate that
+    // is not part of a pair.
+    InvalidCodePoint(u32),
+    // "\u" not followed by four hexadecimal digits ("Fallback::Utf16").
+    InvalidUtf16Escape,
+    // The escape character of "Fallback::Percent" not followed by two hexadecimal digits.
+    InvalidPercentEscape,
+    // An octal escape sequence above "\377" ("Fallback::Octal").
+    InvalidOctalEscape,
+    // Byte escape sequences that are not the UTF-8 encoding of a character ("Fallback::Octal"
+    // and "Fallback::Percent").
+    InvalidUtf8,
+    // A quoted literal without its opening or closing quote (see "dialect.rs").
+    MissingQuote,
+    // A quote that is not escaped, inside a quoted literal (or in an unquoted CSV field).
+    UnexpectedQuote,
+}
+
+// An error of "unescape()". The position is the one of the backslash that starts the faulty
+// escape sequence, in the escaped text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    // The character that starts the escape sequences: '\\', or the one of an "EscapeScheme".
+    pub escaper: char,
+    // Offsets from the start of the text, from 0.
+    pub byte_offset: usize,
+    pub char_offset: usize,
+    // Line and column (in characters), from 1. The lines are separated by '\n'.
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Error {
+    // The description of the error, without its position.
+    pub(crate) fn write_message(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let escaper = self.escaper;
+        match self.kind {
+            ErrorKind::DanglingBackslash if escaper == '\\' => write!(f, "dangling backslash at the end of the text")?,
+            ErrorKind::DanglingBackslash => write!(f, "dangling escape character \"{}\" at the end of the text", escaper)?,
+            ErrorKind::UnknownEscape(c) => write!(f, "unknown escape sequence \"{}{}\"", escaper, c.escape_debug())?,
+            ErrorKind::RawLineBreak(c) => write!(f, "raw line break \"{}\": it must be escaped", c.escape_debug())?,
+            ErrorKind::InvalidHexEscape => write!(f, "\"{}x\" must be followed by two hexadecimal digits", escaper)?,
+            ErrorKind::HexEscapeOutOfRange(value) => write!(
+                f,
+                "\"{0}x{1:02x}\" is out of range: \"{0}x\" escapes must be at most \"{0}x7f\"",
+                escaper, value
+            )?,
+            ErrorKind::InvalidUnicodeEscape => {
+                write!(f, "\"{}u\" must be followed by 1 to 6 hexadecimal digits between braces", escaper)?
+            }
+            ErrorKind::InvalidCodePoint(value) if (0xd800..0xe000).contains(&value) => {
+                write!(f, "\"{}u{{{:x}}}\" is a surrogate, not a character", escaper, value)?
+            }
+            ErrorKind::InvalidCodePoint(value) => {
+                write!(f, "\"{0}u{{{1:x}}}\" is above \"{0}u{{10ffff}}\"", escaper, value)?
+            }
+            ErrorKind::InvalidUtf16Escape => write!(f, "\"{}u\" must be followed by four hexadecimal digits", escaper)?,
+            ErrorKind::InvalidPercentEscape => write!(f, "\"{}\" must be followed by two hexadecimal digits", escaper)?,
+            ErrorKind::InvalidOctalEscape => write!(f, "octal escape sequences must be at most \"{}377\"", escaper)?,
+            ErrorKind::InvalidUtf8 => write!(f, "the escaped bytes are not a UTF-8 character")?,
+            ErrorKind::MissingQuote => write!(f, "missing quote")?,
+            ErrorKind::UnexpectedQuote => write!(f, "unexpected quote: quotes must be escaped")?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_message(f)?;
+        write!(f, " (line {}, column {}, byte offset {})", self.line, self.column, self.byte_offset)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// The position of the next character of the escaped text.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Position {
+    pub(crate) byte_offset: usize,
+    pub(crate) char_offset: usize,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Default for Position {
+    fn default() -> Position {
+        Position {
+            byte_offset: 0,
+            char_offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Position {
+    pub(crate) fn advance(&mut self, c: char) {
+        self.byte_offset += c.len_utf8();
+        self.char_offset += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+
+    // Same as "advance()", one byte of UTF-8 at a time: only the first byte of a character
+    // counts as a character.
+    pub(crate) fn advance_byte(&mut self, byte: u8) {
+        self.byte_offset += 1;
+        if byte == b'\n' {
+            self.char_offset += 1;
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0b1100_0000 != 0b1000_0000 {
+            self.char_offset += 1;
+            self.column += 1;
+        }
+    }
+
+    pub(crate) fn error(self, kind: ErrorKind) -> Error {
+        Error {
+            kind,
+            escaper: '\\',
+            byte_offset: self.byte_offset,
+            char_offset: self.char_offset,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
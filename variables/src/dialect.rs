@@ -0,0 +1,224 @@
+// Built-in escaping dialects, for the usual formats:
+//
+//     dialect    say "hi", it's 5% is written
+//     Json       "say \"hi\", it's 5%"                a JSON string, with its quotes
+//     C          "say \"hi\", it's 5%"                a C string literal, with its quotes
+//     Shell      'say "hi", it'\''s 5%'               a single-quoted POSIX shell word
+//     Csv        "say ""hi"", it's 5%"                an RFC 4180 CSV field, quoted if needed
+//     Percent    say%20%22hi%22%2C%20it%27s%205%25    a URL component, percent-encoded
+//
+// All but "Shell" are "EscapeScheme"s (see "scheme.rs"): only the quotes around the literal are
+// handled here. Inside single quotes, the shell has no escape sequence at all: a quote is written
+// by closing the quotes, writing "\'" and opening them again.
+//
+// "decode()" is the reverse of "encode()": for every dialect and every string "text",
+// "dialect.decode(&dialect.encode(text)) == Ok(text)". It also accepts the other usual forms of
+// each format: "\/" and "\uXXXX" in JSON, "\'", "\?", short octal sequences ("\0") and
+// hexadecimal ones ("\x41", "\xc3\xa9") in C, fields quoted without need in CSV, lowercase
+// hexadecimal digits in percent-encoding... The errors are "codec::Error"s, positioned in the
+// literal.
+//
+// Notes:
+//
+// - In C, the control characters are written in octal, one sequence per UTF-8 byte ("\302\205"
+//   for U+0085): it is what a C compiler stores for this character. The other characters are
+//   kept, as UTF-8.
+// - A shell argument cannot contain '\0': "Shell" encodes it, but the shell would cut the word.
+
+use std::sync::OnceLock;
+
+use crate::codec::{Error, ErrorKind, Position};
+use crate::scheme::{EscapeScheme, Fallback, Options, SchemeError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    Json,
+    C,
+    Shell,
+    Csv,
+    Percent,
+}
+
+impl Dialect {
+    pub const ALL: [Dialect; 5] = [Dialect::Json, Dialect::C, Dialect::Shell, Dialect::Csv, Dialect::Percent];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::Json => "json",
+            Dialect::C => "c",
+            Dialect::Shell => "shell",
+            Dialect::Csv => "csv",
+            Dialect::Percent => "percent",
+        }
+    }
+
+    // The scheme of the text between the quotes. "None" for "Shell".
+    pub fn scheme(self) -> Option<&'static EscapeScheme> {
+        static JSON: OnceLock<Result<EscapeScheme, SchemeError>> = OnceLock::new();
+        static C: OnceLock<Result<EscapeScheme, SchemeError>> = OnceLock::new();
+        static CSV: OnceLock<Result<EscapeScheme, SchemeError>> = OnceLock::new();
+        static PERCENT: OnceLock<Result<EscapeScheme, SchemeError>> = OnceLock::new();
+        let scheme = match self {
+            Dialect::Json => JSON.get_or_init(|| {
+                let mnemonics = [('"', '"'), ('\u{8}', 'b'), ('\u{c}', 'f'), ('\n', 'n'), ('\r', 'r'), ('\t', 't')];
+                let options = Options {
+                    aliases: &[('/', '/')],
+                    ..Options::default()
+                };
+                EscapeScheme::with_options('\\', &mnemonics, Fallback::Utf16, options)
+            }),
+            Dialect::C => C.get_or_init(|| {
+                let mnemonics = [
+                    ('\u{7}', 'a'),
+                    ('\u{8}', 'b'),
+                    ('\u{c}', 'f'),
+                    ('\n', 'n'),
+                    ('\r', 'r'),
+                    ('\t', 't'),
+                    ('\u{b}', 'v'),
+                    ('"', '"'),
+                ];
+                let options = Options {
+                    aliases: &[('\'', '\''), ('?', '?')],
+                    ..Options::default()
+                };
+                EscapeScheme::with_options('\\', &mnemonics, Fallback::Octal, options)
+            }),
+            Dialect::Shell => return None,
+            // Inside the quotes, only the quote is escaped, by doubling it.
+            Dialect::Csv => CSV.get_or_init(|| EscapeScheme::new('"', &[], Fallback::Keep)),
+            // RFC 3986: everything but the unreserved characters is encoded.
+            Dialect::Percent => PERCENT.get_or_init(|| {
+                let options = Options {
+                    fallback_for: |c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')),
+                    ..Options::default()
+                };
+                EscapeScheme::with_options('%', &[], Fallback::Percent, options)
+            }),
+        };
+        Some(scheme.as_ref().expect("the built-in schemes are not ambiguous"))
+    }
+
+    pub fn encode(self, text: &str) -> String {
+        match self {
+            Dialect::Json | Dialect::C => format!("\"{}\"", self.scheme_of_quoted().escape(text)),
+            Dialect::Shell => format!("'{}'", text.replace('\'', "'\\''")),
+            Dialect::Csv if text.contains([',', '"', '\r', '\n']) => {
+                format!("\"{}\"", self.scheme_of_quoted().escape(text))
+            }
+            Dialect::Csv => text.to_string(),
+            Dialect::Percent => self.scheme_of_quoted().escape(text),
+        }
+    }
+
+    pub fn decode(self, literal: &str) -> Result<String, Error> {
+        match self {
+            Dialect::Json | Dialect::C => {
+                let inner = unquote(literal, '"')?;
+                check_quotes(inner)?;
+                self.scheme_of_quoted().unescape(inner).map_err(after_quote)
+            }
+            Dialect::Shell => decode_shell(literal),
+            Dialect::Csv if literal.starts_with('"') => {
+                let inner = unquote(literal, '"')?;
+                self.scheme_of_quoted().unescape(inner).map_err(after_quote)
+            }
+            // Without quotes around the field, a quote is an error.
+            Dialect::Csv => match literal.find('"') {
+                Some(index) => {
+                    let mut position = Position::default();
+                    literal[..index].chars().for_each(|c| position.advance(c));
+                    Err(Error {
+                        escaper: '"',
+                        ..position.error(ErrorKind::UnexpectedQuote)
+                    })
+                }
+                None => Ok(literal.to_string()),
+            },
+            Dialect::Percent => self.scheme_of_quoted().unescape(literal),
+        }
+    }
+
+    fn scheme_of_quoted(self) -> &'static EscapeScheme {
+        self.scheme().expect("only Shell has no scheme")
+    }
+}
+
+// The text between the quotes of "literal".
+fn unquote(literal: &str, quote: char) -> Result<&str, Error> {
+    let missing = |text: &str| {
+        let mut position = Position::default();
+        text.chars().for_each(|c| position.advance(c));
+        Err(Error {
+            escaper: quote,
+            ..position.error(ErrorKind::MissingQuote)
+        })
+    };
+    let Some(rest) = literal.strip_prefix(quote) else {
+        return missing("");
+    };
+    match rest.strip_suffix(quote) {
+        Some(inner) => Ok(inner),
+        None => missing(literal),
+    }
+}
+
+// Find the quotes that would end a JSON or C literal early: the ones that are not escaped.
+fn check_quotes(inner: &str) -> Result<(), Error> {
+    let mut position = Position::default();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '"' {
+            return Err(after_quote(position.error(ErrorKind::UnexpectedQuote)));
+        }
+        position.advance(c);
+        if c == '\\' {
+            if let Some(c) = chars.next() {
+                position.advance(c);
+            }
+        }
+    }
+    Ok(())
+}
+
+// The position of an error found in the text between the quotes, in the literal.
+fn after_quote(error: Error) -> Error {
+    Error {
+        byte_offset: error.byte_offset + 1,
+        char_offset: error.char_offset + 1,
+        column: if error.line == 1 { error.column + 1 } else { error.column },
+        ..error
+    }
+}
+
+// A sequence of single-quoted parts ('...') and escaped quotes (\').
+fn decode_shell(literal: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(literal.len());
+    let mut position = Position::default();
+    let mut rest = literal;
+    if rest.is_empty() {
+        return Err(position.error(ErrorKind::MissingQuote));
+    }
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '\'' => {
+                let end = rest[1..].find('\'').ok_or(position.error(ErrorKind::MissingQuote))?;
+                result.push_str(&rest[1..1 + end]);
+                rest[..end + 2].chars().for_each(|c| position.advance(c));
+                rest = &rest[end + 2..];
+            }
+            '\\' => match rest[1..].chars().next() {
+                Some('\'') => {
+                    result.push('\'');
+                    position.advance('\\');
+                    position.advance('\'');
+                    rest = &rest[2..];
+                }
+                Some(c) => return Err(position.error(ErrorKind::UnknownEscape(c))),
+                None => return Err(position.error(ErrorKind::DanglingBackslash)),
+            },
+            _ => return Err(position.error(ErrorKind::MissingQuote)),
+        }
+    }
+    Ok(result)
+}
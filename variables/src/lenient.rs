@@ -0,0 +1,167 @@
+// A lenient "unescape()", for the texts edited by hand (configuration files...): rather than
+// stopping at the first invalid escape sequence, it replaces each one by a replacement text and
+// goes on. The result is the decoded text, and a diagnostic for each invalid sequence.
+//
+// The diagnostics can be printed as rustc prints its errors, with the line of the text and carets
+// under the invalid sequence:
+//
+//     error: unknown escape sequence "\t"
+//      --> settings.txt:1:5
+//       |
+//     1 | abcd\tefgh
+//       |     ^^
+//
+// The raw line breaks are kept: the text may be the lines of a file, where "codec::unescape()"
+// rejects them. For a text without error and without raw '\n' or '\r', the result is the one of
+// "codec::unescape_with()".
+
+use std::fmt;
+
+use crate::codec::{self, Error, ErrorKind, Position, Style};
+
+// An invalid escape sequence. The error gives the position of its first character (the
+// backslash); the span covers the whole sequence, as far as it can be recognized ("\x4" for
+// "\x4g"). Only the span of "\" followed by a line feed includes a line feed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub error: Error,
+    pub byte_length: usize,
+    pub char_length: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Recovered {
+    // The text, with the replacement in place of each invalid escape sequence.
+    pub text: String,
+    // In the order of the text.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Recovered {
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    // The diagnostics, printed as rustc does. "source" is the escaped text (the one given to
+    // "unescape()"), and "origin" names it in the output (a file name, for example).
+    pub fn report<'a>(&'a self, source: &'a str, origin: &'a str) -> Report<'a> {
+        Report {
+            source,
+            origin,
+            diagnostics: &self.diagnostics,
+        }
+    }
+}
+
+// Same as "codec::unescape()", but each invalid escape sequence is replaced by "replacement"
+// ("\u{fffd}", "?" or "" for example).
+pub fn unescape(text: &str, replacement: &str) -> Recovered {
+    unescape_with(text, Style::Linearize, replacement)
+}
+
+pub fn unescape_with(text: &str, style: Style, replacement: &str) -> Recovered {
+    let mut result = String::with_capacity(text.len());
+    let mut diagnostics = Vec::new();
+    let mut position = Position::default();
+    let mut rest = text;
+    while let Some(index) = rest.find('\\') {
+        let (before, sequence) = rest.split_at(index);
+        result.push_str(before);
+        before.chars().for_each(|c| position.advance(c));
+        let length = match codec::decode(sequence, style) {
+            Ok((c, length)) => {
+                result.push(c);
+                length
+            }
+            Err(kind) => {
+                result.push_str(replacement);
+                let length = span(sequence, kind);
+                diagnostics.push(Diagnostic {
+                    error: position.error(kind),
+                    byte_length: length,
+                    char_length: sequence[..length].chars().count(),
+                });
+                length
+            }
+        };
+        sequence[..length].chars().for_each(|c| position.advance(c));
+        rest = &sequence[length..];
+    }
+    result.push_str(rest);
+    Recovered {
+        text: result,
+        diagnostics,
+    }
+}
+
+// The length in bytes of the invalid escape sequence at the start of "sequence" (a backslash).
+fn span(sequence: &str, kind: ErrorKind) -> usize {
+    // The length of the characters at the start of "text" that satisfy "accept".
+    let run = |text: &str, accept: fn(&u8) -> bool| text.bytes().take_while(accept).count();
+    match kind {
+        ErrorKind::DanglingBackslash => 1,
+        ErrorKind::UnknownEscape(c) => 1 + c.len_utf8(),
+        ErrorKind::InvalidHexEscape => 2 + run(&sequence[2..], u8::is_ascii_hexdigit).min(2),
+        ErrorKind::HexEscapeOutOfRange(_) => 4,
+        ErrorKind::InvalidUnicodeEscape | ErrorKind::InvalidCodePoint(_) => match sequence[2..].strip_prefix('{') {
+            Some(body) => {
+                let digits = run(body, |byte| byte.is_ascii_hexdigit() || *byte == b'_');
+                let closed = body[digits..].starts_with('}');
+                3 + digits + usize::from(closed)
+            }
+            None => 2,
+        },
+        _ => 2,
+    }
+}
+
+// The diagnostics printed as rustc does (see "Recovered::report()").
+pub struct Report<'a> {
+    source: &'a str,
+    origin: &'a str,
+    diagnostics: &'a [Diagnostic],
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The width of the line numbers, for the gutter.
+        let last_line = self.diagnostics.iter().map(|diagnostic| diagnostic.error.line).max().unwrap_or(1);
+        let width = last_line.to_string().len();
+        for diagnostic in self.diagnostics {
+            let error = &diagnostic.error;
+            let line = self.source.split('\n').nth(error.line - 1).unwrap_or("");
+            write!(f, "error: ")?;
+            error.write_message(f)?;
+            writeln!(f)?;
+            writeln!(f, "{:width$}--> {}:{}:{}", "", self.origin, error.line, error.column)?;
+            writeln!(f, "{:width$} |", "")?;
+            // The tabulations are shown as 4 spaces, as rustc does.
+            let mut shown = String::new();
+            let mut caret_start: usize = 0;
+            let mut caret_end: usize = 0;
+            for (index, c) in line.chars().enumerate() {
+                let columns = if c == '\t' { 4 } else { 1 };
+                if index < error.column - 1 {
+                    caret_start += columns;
+                }
+                if index < error.column - 1 + diagnostic.char_length {
+                    caret_end += columns;
+                }
+                match c {
+                    '\t' => shown.push_str("    "),
+                    c => shown.push(c),
+                }
+            }
+            // A span at the end of the line ends on its line feed (or the end of the text).
+            let carets = caret_end.saturating_sub(caret_start).max(1);
+            writeln!(f, "{:>width$} | {}", error.line, shown.trim_end_matches('\r'))?;
+            writeln!(f, "{:width$} | {:caret_start$}{}", "", "", "^".repeat(carets))?;
+            writeln!(f)?;
+        }
+        match self.diagnostics.len() {
+            0 => Ok(()),
+            1 => writeln!(f, "error: 1 invalid escape sequence in {}", self.origin),
+            count => writeln!(f, "error: {} invalid escape sequences in {}", count, self.origin),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+// The library part of the crate: what "main.rs" demonstrates, in a form that other programs can
+// reuse.
+//
+// Note: "main.rs" does not declare these modules with "mod", it imports them from the library
+// with "use variables::...".
+
+// Escaping of the line breaks of a text, so that it fits on a single line ("linearized"), and
+// the reverse operation.
+pub mod codec;
+
+// The same codec on streams ("io::Read" and "io::Write"), for texts that do not fit in memory.
+pub mod stream;
+
+// A generic escape engine, driven by a table of escape sequences.
+pub mod scheme;
+
+// Escaping in the formats of JSON, C, the shell, CSV and URLs, built on the engine.
+pub mod dialect;
+
+// An append-only log of records, one linearized record per line.
+pub mod log;
+
+// The codec on bytes and on the strings of the system ("OsStr"), which may not be UTF-8.
+pub mod bytes;
+
+// An "unescape()" that goes on after the errors, and reports them all as rustc does.
+pub mod lenient;
+
+// The correspondence between the positions in a text and in its linearized form.
+pub mod offsets;
@@ -0,0 +1,442 @@
+// An append-only log of records, one record per line.
+//
+// A record is a list of text fields. Each field is linearized, with the tabulation escaped too
+// ("\t"), and followed by a tabulation. The line ends with the CRC-32 of what precedes it, as 8
+// hexadecimal digits:
+//
+//     record                         line
+//     ["user", "alice"]              user\talice\t6616ca16
+//     ["note", "two\nlines"]         note\ttwo\nlines\t9df33826
+//     []                             00000000
+//
+// (the tabulations are real ones, the "\n" is the escape sequence). Without tabulation, a field
+// is written exactly as "codec::escape()" writes it.
+//
+// Writing a record writes a whole line at the end of the file, maybe in several "write" calls. If
+// one fails, "LogWriter::append()" cuts the part already written off (if that fails too, the
+// writer refuses the following records). After a crash, the last line may be incomplete (torn):
+// "LogReader" stops before it, and "LogWriter::open()" cuts it off before appending. The CRC
+// detects the other damages, reported as "Error::Corrupt".
+//
+// "LogWriter" calls "fsync" according to a "SyncPolicy": the records appended since the last
+// "fsync" may be lost by a crash of the system (not by a crash of the program).
+//
+// Also:
+//
+// - "LogReader::follow()" waits for the records appended by another process ("tail -f").
+// - "tail()" returns the last records of a log.
+// - "compact()" keeps the last record of each key (the first field), and replaces the log
+//   atomically. No "LogWriter" must be appending to the log meanwhile.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::codec;
+use crate::scheme::{EscapeScheme, Fallback};
+
+// When "LogWriter" calls "fsync".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncPolicy {
+    // Never: the system writes the data when it wants. The fastest, the least safe.
+    Never,
+    // After every record.
+    Always,
+    // After every "n" records.
+    EveryRecords(usize),
+    // After the first record appended once the duration has elapsed since the last "fsync".
+    // There is no background thread: without new record, nothing is synced.
+    Interval(Duration),
+}
+
+pub struct LogWriter {
+    file: File,
+    policy: SyncPolicy,
+    // Size of the file: the offset of the next record.
+    length: u64,
+    // Records appended since the last "fsync".
+    unsynced: usize,
+    last_sync: Instant,
+    // A failed write left a part of a line that could not be cut off.
+    poisoned: bool,
+}
+
+impl LogWriter {
+    // Open a log to append records, creating it if needed. A torn last line is removed.
+    pub fn open<P: AsRef<Path>>(path: P, policy: SyncPolicy) -> io::Result<LogWriter> {
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        let length = complete_length(&mut file)?;
+        if length < file.metadata()?.len() {
+            file.set_len(length)?;
+            file.sync_data()?;
+        }
+        Ok(LogWriter {
+            file,
+            policy,
+            length,
+            unsynced: 0,
+            last_sync: Instant::now(),
+            poisoned: false,
+        })
+    }
+
+    // Append a record: the offset of its line.
+    pub fn append(&mut self, fields: &[&str]) -> io::Result<u64> {
+        if self.poisoned {
+            return Err(io::Error::other("LogWriter: a torn record could not be removed"));
+        }
+        let line = encode(fields);
+        // The next record would be appended to the part of the line already written.
+        if let Err(error) = self.file.write_all(line.as_bytes()) {
+            self.poisoned = self.file.set_len(self.length).is_err();
+            return Err(error);
+        }
+        let offset = self.length;
+        self.length += line.len() as u64;
+        self.unsynced += 1;
+        let due = match self.policy {
+            SyncPolicy::Never => false,
+            SyncPolicy::Always => true,
+            SyncPolicy::EveryRecords(count) => self.unsynced >= count,
+            SyncPolicy::Interval(interval) => self.last_sync.elapsed() >= interval,
+        };
+        if due {
+            self.sync()?;
+        }
+        Ok(offset)
+    }
+
+    // The number of records appended since the last "fsync": those a crash of the system may lose.
+    pub fn unsynced(&self) -> usize {
+        self.unsynced
+    }
+
+    // Write the appended records to the disk, whatever the policy.
+    pub fn sync(&mut self) -> io::Result<()> {
+        self.file.sync_data()?;
+        self.unsynced = 0;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for LogWriter {
+    // Sync the last records, unless the policy is "Never". The errors are ignored: call "sync()"
+    // to get them.
+    fn drop(&mut self) {
+        if self.unsynced > 0 && self.policy != SyncPolicy::Never {
+            let _ = self.sync();
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    // Offset of the line in the log, and line number (from 1).
+    pub offset: u64,
+    pub line: u64,
+    pub fields: Vec<String>,
+}
+
+// Why a line is not a valid record.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corruption {
+    // The line does not end with 8 hexadecimal digits.
+    MissingChecksum,
+    // The CRC of the line is not the one written at its end.
+    Checksum { expected: u32, actual: u32 },
+    // The line is not UTF-8.
+    InvalidUtf8,
+    // A field is not a valid linearized text.
+    Field(codec::Error),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Corrupt { offset: u64, line: u64, corruption: Corruption },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Corrupt { offset, line, corruption } => {
+                write!(f, "corrupt record at line {} (byte offset {}): ", line, offset)?;
+                match corruption {
+                    Corruption::MissingChecksum => write!(f, "missing checksum"),
+                    Corruption::Checksum { expected, actual } => {
+                        write!(f, "checksum {:08x} expected, the line has {:08x}", expected, actual)
+                    }
+                    Corruption::InvalidUtf8 => write!(f, "invalid UTF-8"),
+                    Corruption::Field(error) => write!(f, "{}", error),
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+// Reads the records of a log. As an iterator, it stops at the end of the log, before a torn
+// line; "next_record()" can then be called again to read the records appended since.
+pub struct LogReader<R: BufRead> {
+    inner: R,
+    // The line being read: incomplete at the end of the log.
+    pending: Vec<u8>,
+    offset: u64,
+    line: u64,
+}
+
+impl LogReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<LogReader<BufReader<File>>> {
+        Ok(LogReader::new(BufReader::new(File::open(path)?)))
+    }
+
+    // Open a log after its last complete line, to read only the records appended from now on
+    // (with "follow()"). The line numbers then count from there.
+    pub fn open_at_end<P: AsRef<Path>>(path: P) -> io::Result<LogReader<BufReader<File>>> {
+        let mut file = File::open(path)?;
+        let offset = complete_length(&mut file)?;
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(LogReader {
+            offset,
+            ..LogReader::new(BufReader::new(file))
+        })
+    }
+}
+
+impl<R: BufRead> LogReader<R> {
+    pub fn new(inner: R) -> LogReader<R> {
+        LogReader {
+            inner,
+            pending: Vec::new(),
+            offset: 0,
+            line: 0,
+        }
+    }
+
+    // The next record, or "None" at the end of the log. After a corrupt line, the reading goes on
+    // with the next line.
+    pub fn next_record(&mut self) -> Result<Option<Record>, Error> {
+        self.inner.read_until(b'\n', &mut self.pending)?;
+        if self.pending.last() != Some(&b'\n') {
+            return Ok(None);
+        }
+        let offset = self.offset;
+        self.offset += self.pending.len() as u64;
+        self.line += 1;
+        let line = self.line;
+        let result = decode(&self.pending[..self.pending.len() - 1]);
+        self.pending.clear();
+        match result {
+            Ok(fields) => Ok(Some(Record { offset, line, fields })),
+            Err(corruption) => Err(Error::Corrupt { offset, line, corruption }),
+        }
+    }
+
+    // The offset of the torn line at the end of the log, if the last line read is incomplete.
+    pub fn torn_line(&self) -> Option<u64> {
+        (!self.pending.is_empty()).then_some(self.offset)
+    }
+
+    // Read the records forever, waiting for new ones at the end of the log: at the end, check
+    // for new records every "poll".
+    pub fn follow(self, poll: Duration) -> Follow<R> {
+        Follow { reader: self, poll }
+    }
+}
+
+impl<R: BufRead> Iterator for LogReader<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        self.next_record().transpose()
+    }
+}
+
+// Iterator returned by "LogReader::follow()". It never ends.
+pub struct Follow<R: BufRead> {
+    reader: LogReader<R>,
+    poll: Duration,
+}
+
+impl<R: BufRead> Iterator for Follow<R> {
+    type Item = Result<Record, Error>;
+
+    fn next(&mut self) -> Option<Result<Record, Error>> {
+        loop {
+            match self.reader.next_record().transpose() {
+                Some(result) => return Some(result),
+                None => thread::sleep(self.poll),
+            }
+        }
+    }
+}
+
+// The last "count" records of a log (fewer if the log is shorter). The log is read backwards from
+// its end, down to the start of these records only: a corrupt line among them is an error, the
+// lines before them are not read. As with "LogReader::open_at_end()", the line numbers count from
+// the first record returned. A torn last line is ignored.
+pub fn tail<P: AsRef<Path>>(path: P, count: usize) -> Result<Vec<Record>, Error> {
+    let mut file = File::open(path)?;
+    let end = complete_length(&mut file)?;
+    // After the "count + 1"-th line feed from the end: the last one ends the last record.
+    let offset = line_start(&mut file, end, count.saturating_add(1))?;
+    file.seek(SeekFrom::Start(offset))?;
+    let reader = LogReader {
+        offset,
+        ..LogReader::new(BufReader::new(file))
+    };
+    reader.take(count).collect()
+}
+
+// What "compact()" did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Compaction {
+    // The number of records removed.
+    pub removed: usize,
+    // The offset of the torn last line of the old log, if there was one: it is not a record, and
+    // it is not copied.
+    pub torn_line: Option<u64>,
+}
+
+// Rewrite a log, keeping only the last record of each key (its first field). The records keep
+// the order of their lines, and the records without field are removed. The new log is written
+// next to the old one ("<name>.compacting"), synced, then renamed over it. A corrupt record stops
+// the compaction, the log is left unchanged.
+//
+// The rename replaces the file, not its content: a reader opened before (a "Follow" for example)
+// keeps reading the old file, and never sees the records appended after the compaction. It must
+// be opened again.
+pub fn compact<P: AsRef<Path>>(path: P) -> Result<Compaction, Error> {
+    let path = path.as_ref();
+    let mut reader = LogReader::open(path)?;
+    let records = (&mut reader).collect::<Result<Vec<Record>, Error>>()?;
+    let mut last = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        if let Some(key) = record.fields.first() {
+            last.insert(key.as_str(), index);
+        }
+    }
+    let file_name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let temporary = path.with_file_name(format!("{}.compacting", file_name.to_string_lossy()));
+    let mut file = File::create(&temporary)?;
+    for (index, record) in records.iter().enumerate() {
+        let key = record.fields.first().map(String::as_str);
+        if key.is_some_and(|key| last[key] == index) {
+            let fields: Vec<&str> = record.fields.iter().map(String::as_str).collect();
+            file.write_all(encode(&fields).as_bytes())?;
+        }
+    }
+    file.sync_all()?;
+    fs::rename(&temporary, path)?;
+    // Make the rename itself durable.
+    #[cfg(unix)]
+    if let Some(directory) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        File::open(directory)?.sync_all()?;
+    }
+    Ok(Compaction {
+        removed: records.len() - last.len(),
+        torn_line: reader.torn_line(),
+    })
+}
+
+// The escape sequences of the fields: those of "codec::escape()", and "\t".
+fn scheme() -> &'static EscapeScheme {
+    static SCHEME: OnceLock<EscapeScheme> = OnceLock::new();
+    SCHEME.get_or_init(|| {
+        EscapeScheme::new('\\', &[('\n', 'n'), ('\r', 'r'), ('\t', 't')], Fallback::Keep)
+            .expect("the scheme is not ambiguous")
+    })
+}
+
+// The line of a record, with its '\n'.
+fn encode(fields: &[&str]) -> String {
+    let mut line = String::new();
+    for field in fields {
+        line.push_str(&scheme().escape(field));
+        line.push('\t');
+    }
+    let checksum = crc32(line.as_bytes());
+    line.push_str(&format!("{:08x}\n", checksum));
+    line
+}
+
+// The fields of a line, without its '\n'.
+fn decode(line: &[u8]) -> Result<Vec<String>, Corruption> {
+    let split = line.len().checked_sub(8).ok_or(Corruption::MissingChecksum)?;
+    let (body, checksum) = line.split_at(split);
+    if !checksum.iter().all(u8::is_ascii_hexdigit) || !(body.is_empty() || body.ends_with(b"\t")) {
+        return Err(Corruption::MissingChecksum);
+    }
+    let expected = u32::from_str_radix(std::str::from_utf8(checksum).expect("hexadecimal digits"), 16)
+        .expect("hexadecimal digits");
+    let actual = crc32(body);
+    if expected != actual {
+        return Err(Corruption::Checksum { expected, actual });
+    }
+    let body = std::str::from_utf8(body).map_err(|_| Corruption::InvalidUtf8)?;
+    body.split_terminator('\t')
+        .map(|field| scheme().unescape(field).map_err(Corruption::Field))
+        .collect()
+}
+
+// The size of the complete lines of a file: the offset after its last '\n' (0 if there is none).
+fn complete_length(file: &mut File) -> io::Result<u64> {
+    let length = file.metadata()?.len();
+    line_start(file, length, 1)
+}
+
+// The offset after the "lines"-th '\n' before "end" (from 1), counting backwards: 0 if there are
+// fewer. The file is read backwards, from "end".
+fn line_start(file: &mut File, mut end: u64, mut lines: usize) -> io::Result<u64> {
+    let mut buffer = [0; 4096];
+    while end > 0 {
+        let start = end.saturating_sub(buffer.len() as u64);
+        let chunk = &mut buffer[..(end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        for (index, _) in chunk.iter().enumerate().rev().filter(|(_, byte)| **byte == b'\n') {
+            lines -= 1;
+            if lines == 0 {
+                return Ok(start + index as u64 + 1);
+            }
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+// CRC-32 (IEEE 802.3, the one of zip and PNG), with a table of the CRCs of the 256 bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut byte = 0;
+        while byte < 256 {
+            let mut crc = byte as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 { 0xedb8_8320 ^ (crc >> 1) } else { crc >> 1 };
+                bit += 1;
+            }
+            table[byte] = crc;
+            byte += 1;
+        }
+        table
+    };
+    !bytes
+        .iter()
+        .fold(!0, |crc, byte| TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
@@ -1,5 +1,13 @@
-use std::ops::{Add, Index, IndexMut};
-use std::collections::HashMap;
+// The tutorial code shows the basics as they are written at first ("2^8-1", a loop with a
+// counter, "return"...): the lints that would rewrite it are turned off.
+#![allow(unused_mut)]
+#![allow(clippy::precedence, clippy::explicit_counter_loop, clippy::assign_op_pattern, clippy::needless_return)]
+
+use std::ops::{Index, IndexMut};
+use variables::{bytes, codec, lenient};
+use variables::dialect::Dialect;
+use variables::offsets::{Location, OffsetMap};
+use variables::scheme::{EscapeScheme, Fallback};
 
 fn main() {
 
@@ -71,82 +79,60 @@ fn main() {
     let mut _big: u64 = small.into();
 
     // Strings
-    fn linearize(text: String) -> String {
-        let mut result: String = String::from("");
-        for c in text.chars() {
-            match c {
-                '\n' => result = result.add("\\n"),
-                '\r' => result = result.add("\\r"),
-                '\\' => result = result.add("\\\\"),
-                _ => result = result.add( c.to_string().as_str()),
-            };
-        };
-        return result;
-    }
-
-    fn unserialize(text: String) -> Result<String, String> {
-      let mut result: String = String::from("");
-        let mut set: bool = false;
-        for c in text.chars() {
-            match c {
-                '\\' => if !set {
-                    set = true;
-                }
-                else {
-                    result = result.add(String::from("\\").as_str());
-                    set = false;
-                },
-                'n' => if set {
-                    result = result.add(String::from("\n").as_str());
-                    set = false;
-                } else {
-                    result = result.add( c.to_string().as_str());
-                },
-                'r' => if set {
-                    result = result.add(String::from("\r").as_str());
-                    set = false;
-                } else {
-                    result = result.add( c.to_string().as_str());
-                },
-                _ => if set {
-                    return Err(String::from("INvalid input!"));
-                } else {
-                    result = result.add(c.to_string().as_str());
-                }
-            };
-        };
-        if set {
-            return Err(String::from("Invalid input!"));
-        }
-        return Ok(result);
-    }
-
-
-    // fn escape(text: String, escaper: char, chars: HashMap<&char, char>) -> String {
-    //     let mut result: String = String::from("");
-    //     for c in text.chars() {
-    //         if c == escaper {
-    //             result = result.add(escaper.to_string().add(escaper.to_string().as_str()));
-    //             continue;
-    //         }
-    //         // if chars.contains_key(&c) {
-    //         //     result = result.add(escaper.to_string().as_str() + c.to_string().as_str());
-    //         //     continue;
-    //         // }
-    //         // result = result.add(&c.to_string().as_str());
-    //     };
-    //     return result;
-    // }
+    //
+    // "codec::escape()" writes a text on a single line ("linearized"): the line breaks and the
+    // backslashes become escape sequences. "codec::unescape()" restores the text. These functions
+    // are defined by the library of the crate (see "src/codec.rs").
+    //
+    // "codec::escape()" returns a "Cow<str>" ("clone on write"): when there is nothing to escape,
+    // it is the input text itself (borrowed), otherwise a new "String" (owned). Both are used as a
+    // "&str".
 
     let intput: String = String::from("abcd\nefgh");
-    let linearized: String = linearize(intput);
+    let linearized = codec::escape(&intput);
     println!("linearized is {}\n", linearized);
-    let unserialized = unserialize(linearized);
+    let unserialized = codec::unescape(&linearized);
 
     match unserialized {
         Ok(value) => println!("==> <{}>", value),
         Err(value) => println!("ERROR <{}>", value)
     }
 
+    // The escape sequences of the Rust literals are also available: the tabulation is "\t".
+    println!("{}", codec::escape_with("tab\tquote\"", codec::Style::Default));
+
+    // Other escape sequences can be described by a scheme: the escaper, the characters to
+    // escape with their mnemonics, and what to do with the other control characters. Here, "^"
+    // starts the escape sequences, and the tabulation is written "^t".
+    let scheme = EscapeScheme::new('^', &[('\n', 'n'), ('\t', 't')], Fallback::Hex).unwrap();
+    let escaped = scheme.escape("a\tb^c\nd\u{7}");
+    println!("{} => {:?}", escaped, scheme.unescape(&escaped));
+
+    // The usual formats are built in: JSON, C, shell, CSV and percent-encoding.
+    for dialect in Dialect::ALL {
+        let encoded = dialect.encode("it's \"5%\"");
+        println!("{:8} {} => {:?}", dialect.name(), encoded, dialect.decode(&encoded));
+    }
+
+    // Bytes that are not UTF-8 (file names, binary data) are escaped as "\xHH".
+    let escaped = bytes::escape(b"caf\xe9\n");
+    println!("{} => {:?}", escaped, bytes::unescape(&escaped));
+
+    // The error tells what is wrong, and where.
+    match codec::unescape("abcd\\tefgh") {
+        Ok(value) => println!("==> <{}>", value),
+        Err(error) => println!("ERROR <{}>", error)
+    }
 
+    // "lenient::unescape()" goes on after the errors: each invalid escape sequence is replaced,
+    // and reported with its position.
+    let edited = "name = abcd\\tefgh\npath = C:\\Users\\x";
+    let recovered = lenient::unescape(edited, "\u{fffd}");
+    println!("==> <{}>", recovered.text);
+    print!("{}", recovered.report(edited, "settings.txt"));
+
+    // "OffsetMap" translates the positions between a text and its linearized form: column 17 of
+    // the linearized line is line 2, column 5 of the text.
+    let map = OffsetMap::escape("first line\nsecond line");
+    println!("{} {:?}", map.escaped(), map.to_raw_location(Location { line: 1, column: 17 }));
 }
@@ -0,0 +1,148 @@
+// The correspondence between the positions in a text ("raw") and in its linearized form
+// ("escaped"), in both directions. A tool that reports an error at column 57 of a linearized line
+// can then point at the right line and column of the original text.
+//
+//     raw        ab      'c' is at line 2, column 1, byte offset 3
+//                cd
+//     escaped    ab\ncd  'c' is at line 1, column 5, byte offset 4
+//
+// Each escape sequence ("\n", "\r", "\\") is 2 bytes for 1 byte of the raw text: the map only
+// stores the raw offsets of the escaped characters, and a lookup is a binary search among them.
+// An offset inside an escape sequence maps to the escaped character.
+//
+// Offsets are in bytes, from 0. Locations are lines and columns (in characters), from 1, as in the
+// errors of "codec". An offset or a location beyond the end of the text is clamped to the end.
+
+use std::borrow::Cow;
+
+use crate::codec::{self, Error};
+
+// A line and a column (in characters), from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct OffsetMap<'a> {
+    raw: Cow<'a, str>,
+    escaped: Cow<'a, str>,
+    // The raw offsets of the escaped characters, in increasing order.
+    escapes: Vec<usize>,
+}
+
+impl<'a> OffsetMap<'a> {
+    // Linearize "raw" (as "codec::escape()"), and map the positions.
+    pub fn escape(raw: &'a str) -> OffsetMap<'a> {
+        let mut escapes = Vec::new();
+        let mut offset = 0;
+        while let Some(index) = codec::find_escaped(&raw.as_bytes()[offset..]) {
+            escapes.push(offset + index);
+            offset += index + 1;
+        }
+        OffsetMap {
+            raw: Cow::Borrowed(raw),
+            escaped: codec::escape(raw),
+            escapes,
+        }
+    }
+
+    // Restore a linearized text (as "codec::unescape()"), and map the positions.
+    pub fn unescape(escaped: &'a str) -> Result<OffsetMap<'a>, Error> {
+        let raw = codec::unescape(escaped)?;
+        // "escaped" is valid: each backslash starts an escape sequence of 2 bytes.
+        let mut escapes = Vec::new();
+        let mut offset = 0;
+        while let Some(index) = escaped[offset..].find('\\') {
+            escapes.push(offset + index - escapes.len());
+            offset += index + 2;
+        }
+        Ok(OffsetMap {
+            raw: Cow::Owned(raw),
+            escaped: Cow::Borrowed(escaped),
+            escapes,
+        })
+    }
+
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn escaped(&self) -> &str {
+        &self.escaped
+    }
+
+    // The offset in the escaped text of the raw offset "offset".
+    pub fn to_escaped(&self, offset: usize) -> usize {
+        let offset = offset.min(self.raw.len());
+        // Each escaped character before "offset" adds one byte.
+        offset + self.escapes.partition_point(|escape| *escape < offset)
+    }
+
+    // The offset in the raw text of the escaped offset "offset".
+    pub fn to_raw(&self, offset: usize) -> usize {
+        let offset = offset.min(self.escaped.len());
+        // The escape sequence number "index" starts at "escape + index" in the escaped text.
+        // "before" is the number of sequences that end before "offset" (binary search).
+        let (mut before, mut after) = (0, self.escapes.len());
+        while before < after {
+            let middle = (before + after) / 2;
+            if self.escapes[middle] + middle + 2 <= offset {
+                before = middle + 1;
+            } else {
+                after = middle;
+            }
+        }
+        match self.escapes.get(before) {
+            // The second byte of the next escape sequence.
+            Some(escape) if escape + before + 1 == offset => *escape,
+            _ => offset - before,
+        }
+    }
+
+    // The location in the escaped text of a location of the raw text.
+    pub fn to_escaped_location(&self, location: Location) -> Location {
+        let offset = self.to_escaped(offset_of(&self.raw, location));
+        location_of(&self.escaped, offset)
+    }
+
+    // The location in the raw text of a location of the escaped text.
+    pub fn to_raw_location(&self, location: Location) -> Location {
+        let offset = self.to_raw(offset_of(&self.escaped, location));
+        location_of(&self.raw, offset)
+    }
+}
+
+// The location of a byte offset of "text".
+pub fn location_of(text: &str, offset: usize) -> Location {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    Location {
+        line: 1 + before.matches('\n').count(),
+        column: 1 + before[line_start..].chars().count(),
+    }
+}
+
+// The byte offset of a location of "text". A column beyond the end of its line gives the end of
+// the line.
+pub fn offset_of(text: &str, location: Location) -> usize {
+    let line_start = match location.line {
+        0 | 1 => 0,
+        line => match text.match_indices('\n').nth(line - 2) {
+            Some((index, _)) => index + 1,
+            None => return text.len(),
+        },
+    };
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    line_start
+        + line
+            .char_indices()
+            .nth(location.column.saturating_sub(1))
+            .map_or(line.len(), |(index, _)| index)
+}
@@ -0,0 +1,343 @@
+// A generic escape engine: the escape sequences are described by an "EscapeScheme" rather than
+// written in the code.
+//
+// A scheme has:
+//
+// - an escaper, the character that starts the escape sequences ('\\' for "codec::escape()"). The
+//   escaper itself is escaped by doubling it.
+// - a table of mnemonics: the character that follows the escaper for each escaped character
+//   ('\n' is written "\n": its mnemonic is 'n').
+// - a fallback, for the control characters that have no mnemonic: kept as they are, or written
+//   with their code ("\x07" or "\u{7}"), or with the code of their UTF-8 bytes ("\302\205",
+//   "%C2%85").
+//
+// "EscapeScheme::with_options()" also chooses the characters that use the fallback (percent-
+// encoding escapes everything but letters, digits and "-._~"), and the escape sequences that are
+// accepted but never written ("\/" in JSON).
+//
+// "unescape()" is derived from the same table. For the result to be unique, the scheme is checked
+// when it is built: each character has at most one mnemonic, and each mnemonic (including the
+// escaper, and the characters that start the sequences of the fallback) stands for one character
+// only. Then, for every string "text", "scheme.unescape(&scheme.escape(text)) == Ok(text)".
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Write as _;
+
+use crate::codec::{self, Error, ErrorKind, Position};
+
+// How "escape()" writes the control characters that have no mnemonic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fallback {
+    // As they are.
+    Keep,
+    // Escaper, 'x' and two hexadecimal digits: "\x07". Only for the characters up to U+00FF, the
+    // other ones are kept.
+    Hex,
+    // Escaper, 'u' and the code point between braces: "\u{7}".
+    Unicode,
+    // Escaper, 'u' and four hexadecimal digits, as in JSON: "\u0007". The characters above U+FFFF
+    // are written as a pair of UTF-16 surrogates: "\ud83d\ude00".
+    Utf16,
+    // Escaper and three octal digits for each UTF-8 byte, as in C: "\007", "\302\205". Shorter
+    // sequences ("\7") and the hexadecimal ones of C ("\x7", "\xc2\x85") are accepted.
+    Octal,
+    // Escaper and two hexadecimal digits for each UTF-8 byte, without mnemonic, as in URLs:
+    // "%07", "%C2%85". The escaper itself uses the fallback ("%25"), rather than being doubled.
+    Percent,
+}
+
+impl Fallback {
+    // Whether "mnemonic" starts the sequences of the fallback.
+    fn reserves(self, mnemonic: char) -> bool {
+        match self {
+            Fallback::Keep => false,
+            Fallback::Hex => mnemonic == 'x',
+            Fallback::Unicode | Fallback::Utf16 => mnemonic == 'u',
+            Fallback::Octal => matches!(mnemonic, '0'..='7' | 'x'),
+            Fallback::Percent => mnemonic.is_ascii_hexdigit(),
+        }
+    }
+}
+
+// The less common settings of a scheme.
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    // The characters without mnemonic that use the fallback. By default, the control characters.
+    pub fallback_for: fn(char) -> bool,
+    // Escape sequences that "unescape()" accepts, but "escape()" never writes: (mnemonic,
+    // character).
+    pub aliases: &'static [(char, char)],
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            fallback_for: char::is_control,
+            aliases: &[],
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EscapeScheme {
+    escaper: char,
+    // Character -> mnemonic, and mnemonic -> character (aliases included). Both contain the
+    // escaper, but with "Fallback::Percent".
+    mnemonics: HashMap<char, char>,
+    characters: HashMap<char, char>,
+    fallback: Fallback,
+    fallback_for: fn(char) -> bool,
+}
+
+// The reason why a scheme would be ambiguous.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemeError {
+    // A character has two mnemonics (or the escaper has a mnemonic: it is always doubled).
+    DuplicateCharacter(char),
+    // Two characters have the same mnemonic (or the mnemonic of a character is the escaper).
+    DuplicateMnemonic(char),
+    // The mnemonic of a character (or the escaper) starts the sequences of the fallback ('x', 'u',
+    // an octal or hexadecimal digit).
+    ReservedMnemonic(char),
+}
+
+impl fmt::Display for SchemeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemeError::DuplicateCharacter(c) => {
+                write!(f, "the character \"{}\" has more than one mnemonic", c.escape_debug())
+            }
+            SchemeError::DuplicateMnemonic(c) => {
+                write!(f, "the mnemonic \"{}\" stands for more than one character", c.escape_debug())
+            }
+            SchemeError::ReservedMnemonic(c) => {
+                write!(f, "the mnemonic \"{}\" is reserved by the fallback", c.escape_debug())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchemeError {}
+
+impl EscapeScheme {
+    // "mnemonics" lists the escaped characters with their mnemonics: [('\n', 'n'), ...].
+    pub fn new(escaper: char, mnemonics: &[(char, char)], fallback: Fallback) -> Result<EscapeScheme, SchemeError> {
+        EscapeScheme::with_options(escaper, mnemonics, fallback, Options::default())
+    }
+
+    pub fn with_options(
+        escaper: char,
+        mnemonics: &[(char, char)],
+        fallback: Fallback,
+        options: Options,
+    ) -> Result<EscapeScheme, SchemeError> {
+        let mut scheme = EscapeScheme {
+            escaper,
+            mnemonics: HashMap::new(),
+            characters: HashMap::new(),
+            fallback,
+            fallback_for: options.fallback_for,
+        };
+        // The mnemonic of the escaper is the escaper.
+        if fallback != Fallback::Percent {
+            if fallback.reserves(escaper) {
+                return Err(SchemeError::ReservedMnemonic(escaper));
+            }
+            scheme.mnemonics.insert(escaper, escaper);
+            scheme.characters.insert(escaper, escaper);
+        }
+        for &(c, mnemonic) in mnemonics {
+            if fallback.reserves(mnemonic) {
+                return Err(SchemeError::ReservedMnemonic(mnemonic));
+            }
+            if scheme.mnemonics.insert(c, mnemonic).is_some() {
+                return Err(SchemeError::DuplicateCharacter(c));
+            }
+            if scheme.characters.insert(mnemonic, c).is_some() {
+                return Err(SchemeError::DuplicateMnemonic(mnemonic));
+            }
+        }
+        for &(mnemonic, c) in options.aliases {
+            if fallback.reserves(mnemonic) {
+                return Err(SchemeError::ReservedMnemonic(mnemonic));
+            }
+            if scheme.characters.insert(mnemonic, c).is_some() {
+                return Err(SchemeError::DuplicateMnemonic(mnemonic));
+            }
+        }
+        Ok(scheme)
+    }
+
+    // The scheme of "codec::escape()".
+    pub fn linearize() -> EscapeScheme {
+        EscapeScheme::new('\\', &[('\n', 'n'), ('\r', 'r')], Fallback::Keep).expect("the scheme is not ambiguous")
+    }
+
+    pub fn escaper(&self) -> char {
+        self.escaper
+    }
+
+    pub fn fallback(&self) -> Fallback {
+        self.fallback
+    }
+
+    // The mnemonic of a character, if it has one.
+    pub fn mnemonic(&self, c: char) -> Option<char> {
+        self.mnemonics.get(&c).copied()
+    }
+
+    pub fn escape(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+            if let Some(mnemonic) = self.mnemonics.get(&c) {
+                result.push(self.escaper);
+                result.push(*mnemonic);
+            } else if c == self.escaper || (self.fallback_for)(c) {
+                self.write_fallback(&mut result, c);
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+
+    fn write_fallback(&self, result: &mut String, c: char) {
+        let escaper = self.escaper;
+        let mut bytes = [0; 4];
+        let mut units = [0; 2];
+        match self.fallback {
+            Fallback::Hex if (c as u32) <= 0xff => write!(result, "{}x{:02x}", escaper, c as u32),
+            Fallback::Unicode => write!(result, "{}u{{{:x}}}", escaper, c as u32),
+            Fallback::Utf16 => c
+                .encode_utf16(&mut units)
+                .iter()
+                .try_for_each(|unit| write!(result, "{}u{:04x}", escaper, unit)),
+            Fallback::Octal => c
+                .encode_utf8(&mut bytes)
+                .bytes()
+                .try_for_each(|byte| write!(result, "{}{:03o}", escaper, byte)),
+            Fallback::Percent => c
+                .encode_utf8(&mut bytes)
+                .bytes()
+                .try_for_each(|byte| write!(result, "{}{:02X}", escaper, byte)),
+            Fallback::Keep | Fallback::Hex => {
+                result.push(c);
+                Ok(())
+            }
+        }
+        .expect("writing into a String does not fail")
+    }
+
+    // Restore a text written by "escape()". The errors are the ones of "codec::unescape()".
+    pub fn unescape(&self, text: &str) -> Result<String, Error> {
+        let mut result = String::with_capacity(text.len());
+        let mut position = Position::default();
+        let mut rest = text;
+        while let Some(index) = rest.find(self.escaper) {
+            let (before, sequence) = rest.split_at(index);
+            result.push_str(before);
+            before.chars().for_each(|c| position.advance(c));
+            let (c, length) = self.decode(&sequence[self.escaper.len_utf8()..]).map_err(|kind| Error {
+                escaper: self.escaper,
+                ..position.error(kind)
+            })?;
+            result.push(c);
+            sequence[..self.escaper.len_utf8() + length].chars().for_each(|c| position.advance(c));
+            rest = &sequence[self.escaper.len_utf8() + length..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    // Decode the escape sequence that follows an escaper: the character, and the length of the
+    // sequence after the escaper.
+    fn decode(&self, sequence: &str) -> Result<(char, usize), ErrorKind> {
+        let mnemonic = sequence.chars().next().ok_or(ErrorKind::DanglingBackslash)?;
+        let rest = &sequence[mnemonic.len_utf8()..];
+        let (c, length) = match (self.characters.get(&mnemonic), self.fallback) {
+            (Some(c), _) => (*c, 0),
+            (None, Fallback::Hex) if mnemonic == 'x' => codec::decode_hex(rest, 0xff)?,
+            (None, Fallback::Unicode) if mnemonic == 'u' => codec::decode_unicode(rest)?,
+            (None, Fallback::Utf16) if mnemonic == 'u' => return self.decode_utf16(rest).map(|(c, length)| (c, 1 + length)),
+            (None, Fallback::Octal | Fallback::Percent) if self.fallback.reserves(mnemonic) => {
+                return self.decode_bytes(sequence)
+            }
+            (None, _) => return Err(ErrorKind::UnknownEscape(mnemonic)),
+        };
+        Ok((c, mnemonic.len_utf8() + length))
+    }
+
+    // The four digits after "\u", and the second surrogate of a pair.
+    fn decode_utf16(&self, digits: &str) -> Result<(char, usize), ErrorKind> {
+        let unit = |digits: &str| {
+            digits
+                .get(..4)
+                .filter(|digits| digits.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                .map(|digits| u32::from_str_radix(digits, 16).expect("checked above"))
+                .ok_or(ErrorKind::InvalidUtf16Escape)
+        };
+        let first = unit(digits)?;
+        if !(0xd800..0xdc00).contains(&first) {
+            return char::from_u32(first).map(|c| (c, 4)).ok_or(ErrorKind::InvalidCodePoint(first));
+        }
+        let second = digits[4..]
+            .strip_prefix(self.escaper)
+            .and_then(|rest| rest.strip_prefix('u'))
+            .map(unit)
+            .transpose()?
+            .filter(|second| (0xdc00..0xe000).contains(second))
+            .ok_or(ErrorKind::InvalidCodePoint(first))?;
+        let c = char::from_u32(0x10000 + ((first - 0xd800) << 10) + (second - 0xdc00)).expect("valid surrogate pair");
+        Ok((c, 4 + self.escaper.len_utf8() + 1 + 4))
+    }
+
+    // The bytes of a character, each one written "\ooo" or "\xHH" ("Fallback::Octal") or "%HH"
+    // ("Fallback::Percent"). "sequence" starts after the first escaper.
+    fn decode_bytes(&self, sequence: &str) -> Result<(char, usize), ErrorKind> {
+        // One byte: its value, and the length of its digits.
+        let byte = |digits: &str| -> Result<(u8, usize), ErrorKind> {
+            if self.fallback == Fallback::Percent {
+                return codec::decode_hex(digits, 0xff)
+                    .map(|(c, length)| (c as u8, length))
+                    .map_err(|_| ErrorKind::InvalidPercentEscape);
+            }
+            if let Some(digits) = digits.strip_prefix('x') {
+                // As in C, all the hexadecimal digits that follow belong to the sequence.
+                let length = digits.bytes().take_while(|byte| byte.is_ascii_hexdigit()).count();
+                let value = digits[..length]
+                    .chars()
+                    .try_fold(0u8, |value, digit| value.checked_mul(16)?.checked_add(digit.to_digit(16)? as u8))
+                    .filter(|_| length > 0)
+                    .ok_or(ErrorKind::InvalidHexEscape)?;
+                return Ok((value, 1 + length));
+            }
+            let length = digits.bytes().take(3).take_while(|byte| matches!(byte, b'0'..=b'7')).count();
+            let value = u32::from_str_radix(&digits[..length], 8).map_err(|_| ErrorKind::InvalidOctalEscape)?;
+            let value = u8::try_from(value).map_err(|_| ErrorKind::InvalidOctalEscape)?;
+            Ok((value, length))
+        };
+        let (first, mut length) = byte(sequence)?;
+        let count = match first {
+            0x00..=0x7f => 1,
+            0xc2..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf4 => 4,
+            _ => return Err(ErrorKind::InvalidUtf8),
+        };
+        let mut bytes = vec![first];
+        for _ in 1..count {
+            let (next, next_length) = sequence[length..]
+                .strip_prefix(self.escaper)
+                .ok_or(ErrorKind::InvalidUtf8)
+                .and_then(byte)?;
+            bytes.push(next);
+            length += self.escaper.len_utf8() + next_length;
+        }
+        let c = std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| text.chars().next())
+            .ok_or(ErrorKind::InvalidUtf8)?;
+        Ok((c, length))
+    }
+}
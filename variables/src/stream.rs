@@ -0,0 +1,219 @@
+// Streaming versions of the codec: the text goes through a buffer of fixed size, thus files of
+// any size can be linearized.
+//
+// - "EscapingWriter" escapes what is written into it, and writes the result into another writer.
+// - "UnescapingReader" reads an escaped text from another reader, and returns the original text.
+//
+// As "codec::unescape()", "UnescapingReader" rejects the raw line breaks.
+//
+// The escaped characters ('\n', '\r' and '\\') are ASCII: they never occur inside the UTF-8
+// encoding of another character. Thus the streams are processed byte by byte, and the buffer
+// boundaries may fall anywhere, even inside a character or an escape sequence.
+//
+// The errors of "UnescapingReader" are "io::Error"s of kind "InvalidData" that wrap a
+// "codec::Error" (see "codec_error()"). Its offsets, line and column count from the start of the
+// stream.
+
+use std::io::{self, Read, Write};
+
+use crate::codec::{self, Error, ErrorKind, Position};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+// If the inner writer fails, a part of the buffer may have been written already: the output is
+// then incomplete, and the writer is poisoned. All the following writes fail.
+pub struct EscapingWriter<W: Write> {
+    inner: W,
+    poisoned: bool,
+}
+
+impl<W: Write> EscapingWriter<W> {
+    pub fn new(inner: W) -> EscapingWriter<W> {
+        EscapingWriter { inner, poisoned: false }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for EscapingWriter<W> {
+    // The whole buffer is escaped and written, or an error is returned and the writer is
+    // poisoned.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.poisoned {
+            return Err(io::Error::other("EscapingWriter: a previous write failed"));
+        }
+        self.write_escaped(buf).inspect_err(|_| self.poisoned = true)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> EscapingWriter<W> {
+    fn write_escaped(&mut self, buf: &[u8]) -> io::Result<()> {
+        let mut rest = buf;
+        while let Some(index) = codec::find_escaped(rest) {
+            self.inner.write_all(&rest[..index])?;
+            self.inner.write_all(match rest[index] {
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                _ => b"\\\\",
+            })?;
+            rest = &rest[index + 1..];
+        }
+        self.inner.write_all(rest)
+    }
+}
+
+pub struct UnescapingReader<R: Read> {
+    inner: R,
+    buffer: Box<[u8]>,
+    // The bytes of the buffer that remain to be decoded.
+    start: usize,
+    end: usize,
+    // The last byte read is a backslash: the escape sequence continues in the next buffer.
+    backslash: bool,
+    // Position of the next byte to decode (of the pending backslash, if any).
+    position: Position,
+    // Once an error is found, it is returned by all the following reads.
+    error: Option<Error>,
+}
+
+impl<R: Read> UnescapingReader<R> {
+    pub fn new(inner: R) -> UnescapingReader<R> {
+        UnescapingReader {
+            inner,
+            buffer: vec![0; BUFFER_SIZE].into_boxed_slice(),
+            start: 0,
+            end: 0,
+            backslash: false,
+            position: Position::default(),
+            error: None,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn fail(&mut self, kind: ErrorKind) -> io::Error {
+        let error = self.position.error(kind);
+        self.error = Some(error);
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+
+    // Read more bytes into the buffer, after the ones that remain to be decoded. Return false at
+    // the end of the stream.
+    fn fill(&mut self) -> io::Result<bool> {
+        if self.start > 0 {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+        }
+        loop {
+            match self.inner.read(&mut self.buffer[self.end..]) {
+                Ok(0) => return Ok(false),
+                Ok(count) => {
+                    self.end += count;
+                    return Ok(true);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    // The unknown escape sequence of the pending backslash: the character after it, read in
+    // full even if it is split across buffers.
+    fn unknown_escape(&mut self) -> io::Result<io::Error> {
+        let length = match self.buffer[self.start] {
+            0x00..=0x7f => 1,
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        };
+        while self.end - self.start < length && self.fill()? {}
+        let bytes = &self.buffer[self.start..self.end.min(self.start + length)];
+        let c = std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|text| text.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+        Ok(self.fail(ErrorKind::UnknownEscape(c)))
+    }
+}
+
+impl<R: Read> Read for UnescapingReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if let Some(error) = self.error {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+        let mut written = 0;
+        while written < out.len() {
+            if self.start == self.end {
+                if written > 0 {
+                    // Return what is decoded, rather than wait for more input.
+                    break;
+                }
+                if !self.fill()? {
+                    if self.backslash {
+                        return Err(self.fail(ErrorKind::DanglingBackslash));
+                    }
+                    break;
+                }
+            }
+            let byte = self.buffer[self.start];
+            if self.backslash {
+                out[written] = match byte {
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b'\\' => b'\\',
+                    // Return what is decoded first: the error comes with the next read.
+                    _ if written > 0 => break,
+                    _ => return Err(self.unknown_escape()?),
+                };
+                self.backslash = false;
+                self.position.advance_byte(b'\\');
+                self.position.advance_byte(byte);
+                written += 1;
+            } else if byte == b'\\' {
+                self.backslash = true;
+            } else if byte == b'\n' || byte == b'\r' {
+                if written > 0 {
+                    break;
+                }
+                return Err(self.fail(ErrorKind::RawLineBreak(byte as char)));
+            } else {
+                // Copy the bytes up to the next backslash or line break at once.
+                let available = &self.buffer[self.start..self.end];
+                let available = &available[..available.len().min(out.len() - written)];
+                let length = codec::find_escaped(available).unwrap_or(available.len());
+                out[written..written + length].copy_from_slice(&available[..length]);
+                for byte in &available[..length] {
+                    self.position.advance_byte(*byte);
+                }
+                written += length;
+                self.start += length;
+                continue;
+            }
+            self.start += 1;
+        }
+        Ok(written)
+    }
+}
+
+// The "codec::Error" of an error returned by "UnescapingReader", if it is one.
+pub fn codec_error(error: &io::Error) -> Option<&Error> {
+    error.get_ref()?.downcast_ref::<Error>()
+}
@@ -0,0 +1,123 @@
+// The linearize codec on bytes: the exact bytes are restored, and the valid UTF-8 is escaped as
+// "codec::escape()" does.
+
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
+use variables::bytes;
+use variables::codec::{self, ErrorKind};
+
+// Every byte string of at most "length" bytes taken from "alphabet".
+fn byte_strings(alphabet: &[u8], length: usize) -> Vec<Vec<u8>> {
+    let mut strings = vec![Vec::new()];
+    let mut last = vec![Vec::new()];
+    for _ in 0..length {
+        last = last
+            .iter()
+            .flat_map(|prefix: &Vec<u8>| alphabet.iter().map(move |byte| [prefix.as_slice(), &[*byte]].concat()))
+            .collect();
+        strings.extend(last.iter().cloned());
+    }
+    strings
+}
+
+// ASCII, the escaped characters, the bytes of 'é' (0xc3 0xa9) and of a surrogate (0xed 0xa0
+// 0x80), and bytes that are never UTF-8.
+const ALPHABET: &[u8] = b"ax\\\n\r\xc3\xa9\xed\xa0\x80\xff";
+
+#[test]
+fn exact_bytes_are_restored() {
+    for text in byte_strings(ALPHABET, 4) {
+        let escaped = bytes::escape(&text);
+        assert!(!escaped.contains(['\n', '\r']), "{:?}", escaped);
+        assert_eq!(bytes::unescape(&escaped), Ok(text));
+    }
+}
+
+#[test]
+fn valid_utf8_is_escaped_as_the_codec_does() {
+    for text in byte_strings(ALPHABET, 4) {
+        if let Ok(text) = std::str::from_utf8(&text) {
+            assert_eq!(bytes::escape(text.as_bytes()), codec::escape(text));
+            assert_eq!(bytes::unescape(&codec::escape(text)).as_deref(), Ok(text.as_bytes()));
+        }
+    }
+}
+
+#[test]
+fn valid_utf8_without_escape_is_borrowed() {
+    for text in ["", "plain", "é日本"] {
+        match bytes::escape(text.as_bytes()) {
+            Cow::Borrowed(escaped) => assert!(std::ptr::eq(escaped.as_bytes(), text.as_bytes())),
+            Cow::Owned(escaped) => panic!("{:?} was copied into {:?}", text, escaped),
+        }
+    }
+    assert!(matches!(bytes::escape(b"a\nb"), Cow::Owned(_)));
+    assert!(matches!(bytes::escape(b"ab\xff"), Cow::Owned(_)));
+}
+
+#[test]
+fn invalid_sequences() {
+    // At the start, in the middle, at the end.
+    let cases: &[(&[u8], &str)] = &[
+        (b"\xffabc", "\\xffabc"),
+        (b"ab\xe9\ncd", "ab\\xe9\\ncd"),
+        (b"caf\xc3", "caf\\xc3"),
+        (b"\xed\xa0\x80", "\\xed\\xa0\\x80"),
+        (b"\xc3\xa9\xc3", "é\\xc3"),
+    ];
+    for (text, escaped) in cases {
+        assert_eq!(bytes::escape(text), *escaped);
+        assert_eq!(bytes::unescape(escaped).as_deref(), Ok(*text));
+    }
+}
+
+#[test]
+fn literal_hex_escape_in_the_text() {
+    // The text "\x41" (4 characters) is not "A".
+    assert_eq!(bytes::escape(b"\\x41"), "\\\\x41");
+    assert_eq!(bytes::unescape("\\\\x41").as_deref(), Ok(&b"\\x41"[..]));
+    // "\xHH" is accepted for any byte.
+    assert_eq!(bytes::unescape("\\x41\\x0a").as_deref(), Ok(&b"A\n"[..]));
+}
+
+#[test]
+fn errors() {
+    let error = bytes::unescape("ab\\x4g").unwrap_err();
+    assert_eq!((error.kind, error.byte_offset), (ErrorKind::InvalidHexEscape, 2));
+    let error = bytes::unescape("\\xffé\\q").unwrap_err();
+    assert_eq!((error.kind, error.byte_offset, error.column), (ErrorKind::UnknownEscape('q'), 6, 6));
+    let error = bytes::unescape("a\\xff\nb").unwrap_err();
+    assert_eq!((error.kind, error.byte_offset), (ErrorKind::RawLineBreak('\n'), 5));
+    assert_eq!(bytes::unescape("a\\").unwrap_err().kind, ErrorKind::DanglingBackslash);
+}
+
+#[cfg(unix)]
+#[test]
+fn os_strings_are_restored() {
+    use std::os::unix::ffi::OsStrExt;
+
+    for text in byte_strings(ALPHABET, 3) {
+        let text = OsStr::from_bytes(&text);
+        let escaped = bytes::escape_os_str(text);
+        assert_eq!(bytes::unescape_os_str(&escaped).as_deref(), Ok(text));
+    }
+}
+
+#[cfg(windows)]
+#[test]
+fn unpaired_surrogates_are_restored() {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+
+    for wide in [&[0xd800][..], &[0x61, 0xdc00], &[0xdc00, 0xd800], &[0xd83d, 0xde00]] {
+        let text = OsString::from_wide(wide);
+        let escaped = bytes::escape_os_str(&text);
+        assert_eq!(bytes::unescape_os_str(&escaped), Ok(text));
+    }
+    // Not a string of the system: a surrogate pair written as two code points.
+    let error = bytes::unescape_os_str("\\xed\\xa0\\xbd\\xed\\xb8\\x80").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidUtf8);
+    assert_eq!(bytes::unescape_os_str("\\xff").unwrap_err().kind, ErrorKind::InvalidUtf8);
+    assert_eq!(bytes::unescape_os_str("a\\x41").as_deref(), Ok(OsStr::new("aA")));
+}
@@ -0,0 +1,123 @@
+// The round-trip guarantees of the linearize codec (see the header of "src/codec.rs").
+
+use std::borrow::Cow;
+
+use variables::codec::{self, ErrorKind, Style};
+
+// Every string of at most "length" characters taken from "alphabet".
+fn strings(alphabet: &[char], length: usize) -> Vec<String> {
+    let mut strings = vec![String::new()];
+    let mut last = vec![String::new()];
+    for _ in 0..length {
+        last = last
+            .iter()
+            .flat_map(|prefix| alphabet.iter().map(move |c| format!("{}{}", prefix, c)))
+            .collect();
+        strings.extend(last.iter().cloned());
+    }
+    strings
+}
+
+// The characters of the escape sequences, the escaped ones, and a few others.
+const ALPHABET: &[char] = &['a', '\\', 'n', 'r', 't', '\n', '\r', 'é'];
+
+#[test]
+fn unescape_restores_the_text() {
+    for text in strings(ALPHABET, 5) {
+        let escaped = codec::escape(&text);
+        assert!(!escaped.contains(['\n', '\r']), "{:?}", escaped);
+        assert_eq!(codec::unescape(&escaped).as_deref(), Ok(text.as_str()));
+    }
+}
+
+#[test]
+fn texts_without_escape_are_borrowed() {
+    for text in ["", "plain", "é\ttab", "日本語"] {
+        match codec::escape(text) {
+            Cow::Borrowed(escaped) => assert!(std::ptr::eq(escaped, text)),
+            Cow::Owned(escaped) => panic!("{:?} was copied into {:?}", text, escaped),
+        }
+        assert!(matches!(codec::escape_with(text, Style::Linearize), Cow::Borrowed(_)));
+    }
+    for text in ["a\\nb", "\\\\", "end\\r"] {
+        let raw = codec::unescape(text).unwrap();
+        assert!(matches!(codec::escape(&raw), Cow::Owned(escaped) if escaped == text));
+    }
+}
+
+#[test]
+fn accepted_texts_are_escaped_forms() {
+    let mut accepted = 0;
+    for escaped in strings(ALPHABET, 5) {
+        if let Ok(text) = codec::unescape(&escaped) {
+            assert_eq!(codec::escape(&text), escaped);
+            accepted += 1;
+        }
+    }
+    // Not only the texts without backslash.
+    assert!(accepted > 5_000, "{}", accepted);
+}
+
+#[test]
+fn raw_line_breaks_are_rejected() {
+    let error = codec::unescape("ab\\\\c\nd").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::RawLineBreak('\n'));
+    assert_eq!((error.byte_offset, error.char_offset, error.line, error.column), (5, 5, 1, 6));
+    let error = codec::unescape("é\r").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::RawLineBreak('\r'));
+    assert_eq!((error.byte_offset, error.char_offset, error.column), (2, 1, 2));
+    // The Rust styles accept them, as the string literals do.
+    assert_eq!(codec::unescape_with("a\nb", Style::Default).as_deref(), Ok("a\nb"));
+}
+
+// Characters of every kind: ASCII, controls, accents (grapheme extenders), surrogates' neighbors,
+// private use, emoji, the last code points.
+fn sample() -> impl Iterator<Item = char> {
+    (0..0x3100)
+        .chain(0xd7f0..0xe010)
+        .chain(0xfe00..0x10000)
+        .chain(0x1f600..0x1f650)
+        .chain(0xe0000..0xe0200)
+        .chain(0x10fff0..=0x10ffff)
+        .filter_map(char::from_u32)
+}
+
+// NUL, DEL, lone combining marks (at the start, in a row, after a letter), zero-width joiner,
+// variation selector, non-BMP characters, the quotes and the backslash.
+const TRICKY: &[&str] = &[
+    "\0",
+    "a\0b\0",
+    "\u{7f}",
+    "\u{301}",
+    "\u{301}\u{308}a",
+    "e\u{301}",
+    "\u{200d}",
+    "\u{1f600}\u{fe0f}",
+    "\u{10000}\u{10ffff}",
+    "\u{e0001}",
+    "'\"\\{}",
+    "\t\n\r\u{1b}[0m",
+];
+
+#[test]
+fn rust_styles_round_trip() {
+    let texts = TRICKY
+        .iter()
+        .map(|text| text.to_string())
+        .chain(sample().flat_map(|c| [c.to_string(), format!("a{}", c), format!("{}{}", c, c)]));
+    for text in texts {
+        for style in [Style::Default, Style::Debug] {
+            let escaped = codec::escape_with(&text, style);
+            assert!(!escaped.contains(['\0', '\n', '\r', '\u{7f}']), "{:?}", escaped);
+            assert_eq!(codec::unescape_with(&escaped, style).as_deref(), Ok(text.as_str()), "{:?}", escaped);
+        }
+        // "Style::Default" only writes ASCII.
+        assert!(codec::escape_with(&text, Style::Default).is_ascii());
+        assert_eq!(codec::unescape(&codec::escape(&text)).as_deref(), Ok(text.as_str()));
+    }
+    // The escapes of "Style::Debug" are the minimal ones: a combining mark is only escaped at
+    // the start, where it has no character to combine with.
+    assert_eq!(codec::escape_with("\u{301}a\u{301}", Style::Debug), "\\u{301}a\u{301}");
+    assert_eq!(codec::escape_with("\0\u{7f}😀", Style::Debug), "\\0\\u{7f}😀");
+    assert_eq!(codec::escape_with("\0\u{7f}😀", Style::Default), "\\u{0}\\u{7f}\\u{1f600}");
+}
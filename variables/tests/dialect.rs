@@ -0,0 +1,93 @@
+// Round trips of the built-in dialects, and their outputs for a few well-known texts.
+
+use variables::codec::ErrorKind;
+use variables::dialect::Dialect;
+
+// Texts that exercise the escape sequences of every dialect.
+const TEXTS: &[&str] = &[
+    "",
+    "plain",
+    "it's",
+    "say \"hi\", it's 5%",
+    "line 1\nline 2\r\n",
+    "tab\there, bell\u{7}, nul\0, delete\u{7f}",
+    "back\\slash and /slash/",
+    "'quoted' ''twice''",
+    "\"\"",
+    "??=",
+    "é, 日本語, \u{85}, \u{2028}",
+    "emoji \u{1f600}",
+    "%41 %%",
+    "a,b,c",
+    "trailing backslash\\",
+];
+
+#[test]
+fn round_trips() {
+    for dialect in Dialect::ALL {
+        for text in TEXTS {
+            let encoded = dialect.encode(text);
+            assert_eq!(dialect.decode(&encoded).as_deref(), Ok(*text), "{} {:?}", dialect.name(), encoded);
+        }
+    }
+}
+
+#[test]
+fn every_character_round_trips() {
+    let text: String = (0..0x800).chain(0xfff0..0x10010).filter_map(char::from_u32).collect();
+    for dialect in Dialect::ALL {
+        assert_eq!(dialect.decode(&dialect.encode(&text)).as_deref(), Ok(text.as_str()), "{}", dialect.name());
+    }
+}
+
+#[test]
+fn outputs() {
+    let text = "say \"hi\", it's 5%";
+    assert_eq!(Dialect::Json.encode(text), r#""say \"hi\", it's 5%""#);
+    assert_eq!(Dialect::C.encode(text), r#""say \"hi\", it's 5%""#);
+    assert_eq!(Dialect::Shell.encode(text), r#"'say "hi", it'\''s 5%'"#);
+    assert_eq!(Dialect::Csv.encode(text), r#""say ""hi"", it's 5%""#);
+    assert_eq!(Dialect::Percent.encode(text), "say%20%22hi%22%2C%20it%27s%205%25");
+
+    assert_eq!(Dialect::Json.encode("\u{1}\t\u{1f600}"), r#""\u0001\t😀""#);
+    assert_eq!(Dialect::C.encode("\u{1}\t\u{85}é"), r#""\001\t\302\205é""#);
+    assert_eq!(Dialect::Csv.encode("plain"), "plain");
+    assert_eq!(Dialect::Csv.encode("two\nlines"), "\"two\nlines\"");
+    assert_eq!(Dialect::Percent.encode("a-b_c.d~é"), "a-b_c.d~%C3%A9");
+}
+
+#[test]
+fn other_usual_forms() {
+    assert_eq!(Dialect::Json.decode(r#""\/é😀""#).as_deref(), Ok("/é\u{1f600}"));
+    assert_eq!(Dialect::C.decode(r#""\'\?\0\7\101""#).as_deref(), Ok("'?\0\u{7}A"));
+    assert_eq!(Dialect::C.decode(r#""\x41\x7\xc3\xA9\303\xa9""#).as_deref(), Ok("A\u{7}éé"));
+    assert_eq!(Dialect::Shell.decode(r#"'a'\''b'''"#).as_deref(), Ok("a'b"));
+    assert_eq!(Dialect::Csv.decode("\"plain\"").as_deref(), Ok("plain"));
+    assert_eq!(Dialect::Percent.decode("%c3%a9").as_deref(), Ok("é"));
+}
+
+#[test]
+fn errors() {
+    let kind = |dialect: Dialect, literal: &str| dialect.decode(literal).map_err(|error| (error.kind, error.byte_offset));
+    assert_eq!(kind(Dialect::Json, "plain"), Err((ErrorKind::MissingQuote, 0)));
+    assert_eq!(kind(Dialect::Json, "\"open"), Err((ErrorKind::MissingQuote, 5)));
+    assert_eq!(kind(Dialect::Json, r#""a"b""#), Err((ErrorKind::UnexpectedQuote, 2)));
+    assert_eq!(kind(Dialect::Json, r#""\x41""#), Err((ErrorKind::UnknownEscape('x'), 1)));
+    assert_eq!(kind(Dialect::Json, r#""\u12""#), Err((ErrorKind::InvalidUtf16Escape, 1)));
+    assert_eq!(kind(Dialect::Json, r#""\ud83d""#), Err((ErrorKind::InvalidCodePoint(0xd83d), 1)));
+    assert_eq!(kind(Dialect::C, r#""ab\400""#), Err((ErrorKind::InvalidOctalEscape, 3)));
+    assert_eq!(kind(Dialect::C, r#""\302""#), Err((ErrorKind::InvalidUtf8, 1)));
+    assert_eq!(kind(Dialect::C, r#""a\xg""#), Err((ErrorKind::InvalidHexEscape, 2)));
+    // As in C, "\x414" is a single sequence, above 0xff.
+    assert_eq!(kind(Dialect::C, r#""\x414""#), Err((ErrorKind::InvalidHexEscape, 1)));
+    assert_eq!(kind(Dialect::C, r#""\xff""#), Err((ErrorKind::InvalidUtf8, 1)));
+    assert_eq!(kind(Dialect::Shell, "'a'b"), Err((ErrorKind::MissingQuote, 3)));
+    assert_eq!(kind(Dialect::Shell, "'a"), Err((ErrorKind::MissingQuote, 0)));
+    assert_eq!(kind(Dialect::Shell, r"\n"), Err((ErrorKind::UnknownEscape('n'), 0)));
+    assert_eq!(kind(Dialect::Csv, "a\"b"), Err((ErrorKind::UnexpectedQuote, 1)));
+    assert_eq!(kind(Dialect::Csv, "\"a\"b\""), Err((ErrorKind::UnknownEscape('b'), 2)));
+    assert_eq!(kind(Dialect::Percent, "100%"), Err((ErrorKind::DanglingBackslash, 3)));
+    assert_eq!(kind(Dialect::Percent, "%4g"), Err((ErrorKind::InvalidPercentEscape, 0)));
+    assert_eq!(kind(Dialect::Percent, "%C3"), Err((ErrorKind::InvalidUtf8, 0)));
+    assert_eq!(kind(Dialect::Percent, "%FF"), Err((ErrorKind::InvalidUtf8, 0)));
+}
@@ -0,0 +1,118 @@
+// The lenient "unescape()": every invalid escape sequence is reported, with its span, and replaced.
+
+use variables::codec::{self, ErrorKind, Style};
+use variables::lenient;
+
+// The kind, line, column and length in characters of each diagnostic.
+fn diagnostics(recovered: &lenient::Recovered) -> Vec<(ErrorKind, usize, usize, usize)> {
+    recovered
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.error.kind, diagnostic.error.line, diagnostic.error.column, diagnostic.char_length))
+        .collect()
+}
+
+const SETTINGS: &str = "ok\\n line\n\tx = \\q and \\x4g\nend\\";
+
+#[test]
+fn every_error_is_collected_and_replaced() {
+    let recovered = lenient::unescape(SETTINGS, "?");
+    assert!(!recovered.is_valid());
+    assert_eq!(recovered.text, "ok\n line\n\tx = ? and ?4g\nend?");
+    assert_eq!(
+        diagnostics(&recovered),
+        [
+            (ErrorKind::UnknownEscape('q'), 2, 6, 2),
+            (ErrorKind::UnknownEscape('x'), 2, 13, 2),
+            (ErrorKind::DanglingBackslash, 3, 4, 1),
+        ]
+    );
+    // The positions are those of the errors of "codec".
+    assert_eq!(recovered.diagnostics[0].error.byte_offset, 15);
+    assert_eq!(lenient::unescape(SETTINGS, "").text, "ok\n line\n\tx =  and 4g\nend");
+    assert_eq!(lenient::unescape("a\\qb", "\u{fffd}").text, "a\u{fffd}b");
+}
+
+#[test]
+fn valid_texts_are_those_of_the_codec() {
+    for text in ["", "plain", "a\\nb\\\\c\\r", "é\\n日本"] {
+        let recovered = lenient::unescape(text, "?");
+        assert!(recovered.is_valid());
+        assert_eq!(Ok(recovered.text), codec::unescape(text));
+    }
+    // The raw line breaks are kept.
+    let recovered = lenient::unescape("a\r\nb\\n", "?");
+    assert!(recovered.is_valid());
+    assert_eq!(recovered.text, "a\r\nb\n");
+}
+
+#[test]
+fn spans_cover_the_sequences() {
+    let spans = |text| {
+        let recovered = lenient::unescape_with(text, Style::Default, "?");
+        let diagnostic = recovered.diagnostics[0];
+        (diagnostic.error.kind, diagnostic.byte_length, diagnostic.char_length)
+    };
+    assert_eq!(spans("\\x4g"), (ErrorKind::InvalidHexEscape, 3, 3));
+    assert_eq!(spans("\\xg"), (ErrorKind::InvalidHexEscape, 2, 2));
+    assert_eq!(spans("\\x80"), (ErrorKind::HexEscapeOutOfRange(0x80), 4, 4));
+    assert_eq!(spans("\\u{d800}a"), (ErrorKind::InvalidCodePoint(0xd800), 8, 8));
+    assert_eq!(spans("\\u{12"), (ErrorKind::InvalidUnicodeEscape, 5, 5));
+    assert_eq!(spans("\\u12"), (ErrorKind::InvalidUnicodeEscape, 2, 2));
+    assert_eq!(spans("\\é"), (ErrorKind::UnknownEscape('é'), 3, 2));
+    // Only "\" followed by a line feed spans a line feed.
+    assert_eq!(spans("\\\nb"), (ErrorKind::UnknownEscape('\n'), 2, 2));
+    assert_eq!(spans("\\"), (ErrorKind::DanglingBackslash, 1, 1));
+}
+
+#[test]
+fn report_is_printed_as_rustc_does() {
+    let recovered = lenient::unescape(SETTINGS, "?");
+    let expected = r#"error: unknown escape sequence "\q"
+ --> settings.txt:2:6
+  |
+2 |     x = \q and \x4g
+  |         ^^
+
+error: unknown escape sequence "\x"
+ --> settings.txt:2:13
+  |
+2 |     x = \q and \x4g
+  |                ^^
+
+error: dangling backslash at the end of the text
+ --> settings.txt:3:4
+  |
+3 | end\
+  |    ^
+
+error: 3 invalid escape sequences in settings.txt
+"#;
+    assert_eq!(recovered.report(SETTINGS, "settings.txt").to_string(), expected);
+}
+
+#[test]
+fn report_of_wide_gutters_and_line_ends() {
+    // 11 lines: the gutter is 2 columns wide. The '\r' of a CRLF line is not shown, and a span
+    // that ends on the line feed has a single caret.
+    let source = format!("{}a\\q\r\nb\\\nc", "\n".repeat(9));
+    let recovered = lenient::unescape(&source, "?");
+    let expected = r#"error: unknown escape sequence "\q"
+  --> input:10:2
+   |
+10 | a\q
+   |  ^^
+
+error: unknown escape sequence "\\n"
+  --> input:11:2
+   |
+11 | b\
+   |  ^
+
+error: 2 invalid escape sequences in input
+"#;
+    assert_eq!(recovered.report(&source, "input").to_string(), expected);
+    assert_eq!(lenient::unescape("", "?").report("", "input").to_string(), "");
+    let recovered = lenient::unescape("\\q", "?");
+    assert!(recovered.report("\\q", "x").to_string().ends_with("error: 1 invalid escape sequence in x\n"));
+}
@@ -0,0 +1,198 @@
+// The log: the checksums, the torn last line, the sync policies, "tail()" and "compact()".
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use variables::log::{self, Corruption, Error, LogReader, LogWriter, SyncPolicy};
+
+// A new log file, empty, for the test "name".
+fn log_path(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("variables-log-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(format!("{}.log", name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn write(path: &PathBuf, records: &[&[&str]]) {
+    let mut writer = LogWriter::open(path, SyncPolicy::Never).unwrap();
+    for fields in records {
+        writer.append(fields).unwrap();
+    }
+}
+
+fn fields(records: &[log::Record]) -> Vec<Vec<&str>> {
+    records.iter().map(|record| record.fields.iter().map(String::as_str).collect()).collect()
+}
+
+#[test]
+fn lines_are_the_documented_ones() {
+    let path = log_path("lines");
+    write(&path, &[&["user", "alice"], &["note", "two\nlines"], &[]]);
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(content, "user\talice\t6616ca16\nnote\ttwo\\nlines\t9df33826\n00000000\n");
+    let records = LogReader::open(&path).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(fields(&records), [vec!["user", "alice"], vec!["note", "two\nlines"], vec![]]);
+    assert_eq!((records[1].offset, records[1].line), (20, 2));
+}
+
+#[test]
+fn damaged_lines_are_corrupt() {
+    let path = log_path("damaged");
+    write(&path, &[&["a", "1"], &["b", "2"], &["c", "3"]]);
+    // "b\t2" becomes "b\t3": the CRC no longer matches.
+    let content = fs::read_to_string(&path).unwrap().replacen("b\t2", "b\t3", 1);
+    fs::write(&path, format!("{}no checksum\n", content)).unwrap();
+    let results: Vec<_> = LogReader::open(&path).unwrap().collect();
+    assert_eq!(results.len(), 4);
+    match &results[1] {
+        Err(Error::Corrupt {
+            offset: 13,
+            line: 2,
+            corruption: Corruption::Checksum { .. },
+        }) => {}
+        other => panic!("{:?}", other),
+    }
+    // The reading goes on after a corrupt line.
+    assert_eq!(results[2].as_ref().unwrap().fields, ["c", "3"]);
+    match &results[3] {
+        Err(Error::Corrupt {
+            corruption: Corruption::MissingChecksum,
+            ..
+        }) => {}
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn torn_line_is_skipped_then_cut_off() {
+    let path = log_path("torn");
+    write(&path, &[&["a", "1"]]);
+    let length = fs::metadata(&path).unwrap().len();
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(b"b\t2\t12").unwrap();
+    let mut reader = LogReader::open(&path).unwrap();
+    assert_eq!(reader.next_record().unwrap().unwrap().fields, ["a", "1"]);
+    assert!(reader.next_record().unwrap().is_none());
+    assert_eq!(reader.torn_line(), Some(length));
+    // The writer removes it before appending.
+    let mut writer = LogWriter::open(&path, SyncPolicy::Always).unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), length);
+    assert_eq!(writer.append(&["c", "3"]).unwrap(), length);
+    let records = LogReader::open(&path).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(fields(&records), [vec!["a", "1"], vec!["c", "3"]]);
+}
+
+#[test]
+fn sync_policies() {
+    let path = log_path("sync");
+    let unsynced = |policy, appends| {
+        let mut writer = LogWriter::open(&path, policy).unwrap();
+        (0..appends)
+            .map(|_| {
+                writer.append(&["k"]).unwrap();
+                writer.unsynced()
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(unsynced(SyncPolicy::Never, 3), [1, 2, 3]);
+    assert_eq!(unsynced(SyncPolicy::Always, 3), [0, 0, 0]);
+    assert_eq!(unsynced(SyncPolicy::EveryRecords(2), 5), [1, 0, 1, 0, 1]);
+    assert_eq!(unsynced(SyncPolicy::Interval(Duration::ZERO), 2), [0, 0]);
+    assert_eq!(unsynced(SyncPolicy::Interval(Duration::from_secs(3600)), 2), [1, 2]);
+    let mut writer = LogWriter::open(&path, SyncPolicy::Never).unwrap();
+    writer.append(&["k"]).unwrap();
+    writer.sync().unwrap();
+    assert_eq!(writer.unsynced(), 0);
+    drop(writer);
+    assert_eq!(LogReader::open(&path).unwrap().count(), 16);
+}
+
+#[test]
+fn tail_reads_the_last_records() {
+    let path = log_path("tail");
+    // More than the 4 KiB read at once from the end.
+    let values: Vec<String> = (0..1000).map(|index| index.to_string()).collect();
+    let mut writer = LogWriter::open(&path, SyncPolicy::Never).unwrap();
+    for value in &values {
+        writer.append(&["key", value]).unwrap();
+    }
+    drop(writer);
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(b"torn").unwrap();
+    for count in [0, 1, 2, 999, 1000, 2000] {
+        let records = log::tail(&path, count).unwrap();
+        let expected: Vec<&str> = values[1000 - count.min(1000)..].iter().map(String::as_str).collect();
+        let actual: Vec<&str> = records.iter().map(|record| record.fields[1].as_str()).collect();
+        assert_eq!(actual, expected);
+        // Line numbers from the first record returned.
+        assert!(records.iter().enumerate().all(|(index, record)| record.line == index as u64 + 1));
+    }
+    assert_eq!(log::tail(&path, 1).unwrap()[0].offset, fs::metadata(&path).unwrap().len() - 4 - 17);
+}
+
+#[test]
+fn tail_errors_do_not_depend_on_the_count() {
+    let path = log_path("tail-corrupt");
+    write(&path, &[&["a"], &["b"], &["c"]]);
+    let content = fs::read_to_string(&path).unwrap().replacen("b\t", "x\t", 1);
+    fs::write(&path, content).unwrap();
+    // The second to last line is corrupt: it is read from a count of 2 on.
+    assert_eq!(fields(&log::tail(&path, 0).unwrap()), Vec::<Vec<&str>>::new());
+    assert_eq!(fields(&log::tail(&path, 1).unwrap()), [vec!["c"]]);
+    for count in [2, 3, 10] {
+        assert!(matches!(log::tail(&path, count), Err(Error::Corrupt { .. })), "{}", count);
+    }
+}
+
+#[test]
+fn compact_keeps_the_last_record_of_each_key() {
+    let path = log_path("compact");
+    write(&path, &[&["a", "1"], &["b", "1"], &[], &["a", "2"], &["c", "1"], &["b", "2"]]);
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(b"a\t3").unwrap();
+    let torn = fs::metadata(&path).unwrap().len() - 3;
+    let compaction = log::compact(&path).unwrap();
+    assert_eq!((compaction.removed, compaction.torn_line), (3, Some(torn)));
+    let records = LogReader::open(&path).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(fields(&records), [vec!["a", "2"], vec!["c", "1"], vec!["b", "2"]]);
+    assert!(!path.with_file_name("compact.log.compacting").exists());
+    // A corrupt record leaves the log unchanged.
+    let content = fs::read_to_string(&path).unwrap().replacen("c\t1", "c\t2", 1);
+    fs::write(&path, &content).unwrap();
+    assert!(matches!(log::compact(&path), Err(Error::Corrupt { line: 2, .. })));
+    assert_eq!(fs::read_to_string(&path).unwrap(), content);
+}
+
+// A write that fails after a part of the line: the test runs again in a process whose files are
+// limited to 512 bytes ("ulimit -f 1"), where the write of the line crossing the limit stops there.
+#[cfg(unix)]
+#[test]
+fn failed_append_leaves_no_torn_line() {
+    // The child process writes to the log of its parent.
+    if let Some(path) = std::env::var_os("LOG_TEST_LIMITED") {
+        let mut writer = LogWriter::open(&path, SyncPolicy::Never).unwrap();
+        // 20 lines of 19 bytes: 380 bytes.
+        for index in 0..20 {
+            writer.append(&["record", &format!("{:02}", index)]).unwrap();
+        }
+        let long = "x".repeat(200);
+        assert!(writer.append(&[&long]).is_err());
+        assert_eq!(fs::metadata(&path).unwrap().len(), 380);
+        writer.append(&["last"]).unwrap();
+        return;
+    }
+    let path = log_path("failed-append");
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg("trap '' XFSZ; ulimit -f 1; exec \"$0\" --exact failed_append_leaves_no_torn_line")
+        .arg(std::env::current_exe().unwrap())
+        .env("LOG_TEST_LIMITED", &path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    // The records whose "append()" succeeded, and nothing else.
+    let records = LogReader::open(&path).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 21);
+    assert_eq!(records[20].fields, ["last"]);
+    assert_eq!(fs::metadata(&path).unwrap().len(), records[20].offset + 14);
+}
@@ -0,0 +1,88 @@
+// The offset map, against a map built character by character.
+
+use variables::codec;
+use variables::offsets::{self, Location, OffsetMap};
+
+// The raw offset of each escaped offset, from 0 to the end of the escaped text: the second byte
+// of an escape sequence maps to the escaped character.
+fn raw_offsets(raw: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    for (index, byte) in raw.bytes().enumerate() {
+        offsets.push(index);
+        if matches!(byte, b'\n' | b'\r' | b'\\') {
+            offsets.push(index);
+        }
+    }
+    offsets.push(raw.len());
+    offsets
+}
+
+// Short texts, and a long one for the binary search.
+fn texts() -> Vec<String> {
+    let mut texts: Vec<String> = ["", "plain", "ab\ncd", "\n\r\\", "é\\日\n\n本\r\n", "end\\"].map(String::from).into();
+    texts.push("ab\\\n\ncdé\r".repeat(40));
+    texts
+}
+
+#[test]
+fn to_raw_finds_the_escaped_characters() {
+    for raw in &texts() {
+        let expected = raw_offsets(raw);
+        let escaped = codec::escape(raw);
+        for map in [OffsetMap::escape(raw), OffsetMap::unescape(&escaped).unwrap()] {
+            assert_eq!(map.raw(), raw);
+            for (offset, raw_offset) in expected.iter().enumerate() {
+                assert_eq!(map.to_raw(offset), *raw_offset, "{:?} {}", raw, offset);
+            }
+        }
+    }
+    // The second byte of each sequence.
+    let map = OffsetMap::escape("a\n\\b");
+    assert_eq!(map.escaped(), "a\\n\\\\b");
+    assert_eq!([map.to_raw(2), map.to_raw(4), map.to_raw(5)], [1, 2, 3]);
+}
+
+#[test]
+fn offsets_round_trip() {
+    for raw in &texts() {
+        let map = OffsetMap::escape(raw);
+        let escaped_offsets: Vec<usize> = (0..=raw.len()).map(|offset| map.to_escaped(offset)).collect();
+        assert!(escaped_offsets.windows(2).all(|pair| pair[0] < pair[1]));
+        for offset in 0..=raw.len() {
+            assert_eq!(map.to_raw(map.to_escaped(offset)), offset);
+        }
+    }
+}
+
+#[test]
+fn offsets_are_clamped() {
+    let map = OffsetMap::escape("a\nb");
+    assert_eq!(map.to_escaped(100), 4);
+    assert_eq!(map.to_raw(100), 3);
+    assert_eq!(offsets::location_of("ab\ncd", 100), Location { line: 2, column: 3 });
+    // Inside a character: the character.
+    assert_eq!(offsets::location_of("aé", 2), Location { line: 1, column: 2 });
+    let text = "ab\nc";
+    assert_eq!(offsets::offset_of(text, Location { line: 1, column: 10 }), 2);
+    assert_eq!(offsets::offset_of(text, Location { line: 2, column: 10 }), 4);
+    assert_eq!(offsets::offset_of(text, Location { line: 5, column: 1 }), 4);
+    assert_eq!(offsets::offset_of(text, Location { line: 0, column: 0 }), 0);
+}
+
+#[test]
+fn locations_are_translated() {
+    let map = OffsetMap::escape("ab\ncd");
+    assert_eq!(map.to_escaped_location(Location { line: 2, column: 1 }), Location { line: 1, column: 5 });
+    assert_eq!(map.to_raw_location(Location { line: 1, column: 5 }), Location { line: 2, column: 1 });
+    // The 'n' of "\n" is the line feed, at the end of the first line.
+    assert_eq!(map.to_raw_location(Location { line: 1, column: 4 }), Location { line: 1, column: 3 });
+    let map = OffsetMap::escape("é\n日本");
+    assert_eq!(map.to_raw_location(Location { line: 1, column: 5 }), Location { line: 2, column: 2 });
+    for offset in 0..=map.raw().len() {
+        if map.raw().is_char_boundary(offset) {
+            let location = offsets::location_of(map.raw(), offset);
+            assert_eq!(offsets::offset_of(map.raw(), location), offset);
+            assert_eq!(map.to_raw_location(map.to_escaped_location(location)), location);
+        }
+    }
+}
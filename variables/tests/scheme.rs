@@ -0,0 +1,71 @@
+// The escape engine: the ambiguity checks, and the "unescape()" derived from the table.
+
+use variables::codec::{self, ErrorKind};
+use variables::scheme::{EscapeScheme, Fallback, Options, SchemeError};
+
+#[test]
+fn ambiguous_schemes_are_rejected() {
+    let new = |escaper, mnemonics: &[(char, char)], fallback| EscapeScheme::new(escaper, mnemonics, fallback).err();
+    assert_eq!(new('\\', &[('\n', 'n'), ('\n', 'm')], Fallback::Keep), Some(SchemeError::DuplicateCharacter('\n')));
+    assert_eq!(new('\\', &[('\n', 'n'), ('\t', 'n')], Fallback::Keep), Some(SchemeError::DuplicateMnemonic('n')));
+    // The escaper is its own mnemonic.
+    assert_eq!(new('\\', &[('\n', '\\')], Fallback::Keep), Some(SchemeError::DuplicateMnemonic('\\')));
+    assert_eq!(new('\\', &[('\\', 'b')], Fallback::Keep), Some(SchemeError::DuplicateCharacter('\\')));
+    // The characters that start the sequences of the fallback.
+    assert_eq!(new('\\', &[('\t', 'x')], Fallback::Hex), Some(SchemeError::ReservedMnemonic('x')));
+    assert_eq!(new('\\', &[('\t', 'u')], Fallback::Unicode), Some(SchemeError::ReservedMnemonic('u')));
+    assert_eq!(new('\\', &[('\t', 'u')], Fallback::Utf16), Some(SchemeError::ReservedMnemonic('u')));
+    assert_eq!(new('\\', &[('\0', '0')], Fallback::Octal), Some(SchemeError::ReservedMnemonic('0')));
+    assert_eq!(new('%', &[(' ', 'B')], Fallback::Percent), Some(SchemeError::ReservedMnemonic('B')));
+    assert_eq!(new('x', &[], Fallback::Hex), Some(SchemeError::ReservedMnemonic('x')));
+    // Without fallback, 'x' and 'u' are ordinary mnemonics.
+    assert!(new('\\', &[('\t', 'x'), ('\u{7}', 'u')], Fallback::Keep).is_none());
+    // An alias must not hide a mnemonic.
+    let options = Options {
+        aliases: &[('n', '/')],
+        ..Options::default()
+    };
+    let error = EscapeScheme::with_options('\\', &[('\n', 'n')], Fallback::Keep, options).err();
+    assert_eq!(error, Some(SchemeError::DuplicateMnemonic('n')));
+}
+
+#[test]
+fn linearize_is_the_codec() {
+    let scheme = EscapeScheme::linearize();
+    for text in ["", "plain", "a\nb\r\\c", "\\\\\n"] {
+        assert_eq!(scheme.escape(text), codec::escape(text));
+        assert_eq!(scheme.unescape(&scheme.escape(text)).as_deref(), Ok(text));
+    }
+    for escaped in ["a\\tb", "\\", "é\\q"] {
+        assert_eq!(scheme.unescape(escaped), codec::unescape(escaped));
+    }
+}
+
+#[test]
+fn unescape_is_derived_from_the_table() {
+    let texts = ["", "a^b", "tab\there", "bell\u{7}, nul\0, del\u{7f}", "line\nbreak^^", "é\u{85}日\u{1f600}"];
+    for fallback in [Fallback::Keep, Fallback::Hex, Fallback::Unicode, Fallback::Utf16, Fallback::Octal] {
+        let scheme = EscapeScheme::new('^', &[('\n', 'n'), ('\t', 't')], fallback).unwrap();
+        for text in texts {
+            let escaped = scheme.escape(text);
+            assert!(!escaped.contains(['\n', '\t']), "{:?}", escaped);
+            assert_eq!(scheme.unescape(&escaped).as_deref(), Ok(text), "{:?} {:?}", fallback, escaped);
+        }
+    }
+    let scheme = EscapeScheme::new('^', &[('\n', 'n'), ('\t', 't')], Fallback::Hex).unwrap();
+    assert_eq!(scheme.escape("a\tb^c\nd\u{7}"), "a^tb^^c^nd^x07");
+    assert_eq!(scheme.mnemonic('\t'), Some('t'));
+    assert_eq!(scheme.mnemonic('a'), None);
+}
+
+#[test]
+fn errors_carry_the_escaper() {
+    let scheme = EscapeScheme::new('^', &[('\n', 'n')], Fallback::Hex).unwrap();
+    let error = scheme.unescape("ab\ncd^q").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::UnknownEscape('q'));
+    assert_eq!((error.escaper, error.byte_offset, error.line, error.column), ('^', 5, 2, 3));
+    assert_eq!(error.to_string(), "unknown escape sequence \"^q\" (line 2, column 3, byte offset 5)");
+    let error = scheme.unescape("ab^").unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DanglingBackslash);
+    assert_eq!(scheme.unescape("^x0g").unwrap_err().kind, ErrorKind::InvalidHexEscape);
+}
@@ -0,0 +1,137 @@
+// The streaming codec, fed in small chunks: the chunk boundaries fall inside the escape
+// sequences and the characters.
+
+use std::io::{self, Read, Write};
+
+use variables::codec::{self, ErrorKind};
+use variables::stream::{self, EscapingWriter, UnescapingReader};
+
+// A reader that returns at most "chunk" bytes per read.
+struct Chunked<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl Read for Chunked<'_> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let length = self.data.len().min(self.chunk).min(out.len());
+        out[..length].copy_from_slice(&self.data[..length]);
+        self.data = &self.data[length..];
+        Ok(length)
+    }
+}
+
+// A writer that fails once "capacity" bytes are written.
+struct Full {
+    written: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for Full {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let length = buf.len().min(self.capacity - self.written.len());
+        if length == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "full"));
+        }
+        self.written.extend_from_slice(&buf[..length]);
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn unescape(escaped: &str, chunk: usize) -> Result<String, codec::Error> {
+    let mut reader = UnescapingReader::new(Chunked {
+        data: escaped.as_bytes(),
+        chunk,
+    });
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .map(|_| text)
+        .map_err(|error| *stream::codec_error(&error).expect("a codec error"))
+}
+
+const TEXTS: &[&str] = &[
+    "",
+    "plain",
+    "a\\b\nc\r\nd\\",
+    "\\\\\\\n\n\r",
+    "é\n日本\\語\r\u{1f600}",
+];
+
+#[test]
+fn round_trips_in_chunks() {
+    for text in TEXTS {
+        for chunk in [1, 2, 3, 7] {
+            let mut writer = EscapingWriter::new(Vec::new());
+            for part in text.as_bytes().chunks(chunk) {
+                writer.write_all(part).unwrap();
+            }
+            let escaped = String::from_utf8(writer.into_inner()).unwrap();
+            assert_eq!(escaped, codec::escape(text));
+            assert_eq!(unescape(&escaped, chunk).as_deref(), Ok(*text), "{:?} {}", escaped, chunk);
+        }
+    }
+}
+
+#[test]
+fn backslash_at_the_end_of_a_chunk() {
+    // With chunks of 2 bytes, the backslashes are the last bytes of the first chunks.
+    assert_eq!(unescape("a\\nb\\\\", 2).as_deref(), Ok("a\nb\\"));
+    let error = unescape("a\\", 2).unwrap_err();
+    assert_eq!((error.kind, error.byte_offset), (ErrorKind::DanglingBackslash, 1));
+}
+
+#[test]
+fn multi_byte_character_after_an_unknown_escape() {
+    for chunk in [1, 2, 3] {
+        let error = unescape("ab\\é", chunk).unwrap_err();
+        assert_eq!(error, codec::unescape("ab\\é").unwrap_err());
+        assert_eq!((error.kind, error.byte_offset), (ErrorKind::UnknownEscape('é'), 2));
+        let error = unescape("ab\\\u{1f600}x", chunk).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::UnknownEscape('\u{1f600}'));
+    }
+}
+
+#[test]
+fn positions_are_absolute_across_refills() {
+    // Far beyond the 8 KiB of the buffer, with multi-byte characters and escape sequences.
+    let prefix = "日本\\n\\\\é".repeat(5_000);
+    for (suffix, kind) in [
+        ("\\q", ErrorKind::UnknownEscape('q')),
+        ("\n", ErrorKind::RawLineBreak('\n')),
+        ("\\", ErrorKind::DanglingBackslash),
+    ] {
+        let escaped = format!("{}{}", prefix, suffix);
+        let expected = codec::unescape(&escaped).unwrap_err();
+        assert_eq!(expected.kind, kind);
+        assert!(expected.byte_offset > 40_000 && expected.char_offset < expected.byte_offset);
+        for chunk in [1, 7, 4096, 100_000] {
+            assert_eq!(unescape(&escaped, chunk), Err(expected));
+        }
+    }
+}
+
+#[test]
+fn the_error_is_sticky() {
+    let mut reader = UnescapingReader::new("ab\\q".as_bytes());
+    let mut out = [0; 16];
+    // The decoded bytes first, then the error.
+    assert_eq!(reader.read(&mut out).unwrap(), 2);
+    assert!(reader.read(&mut out).is_err());
+    assert!(reader.read(&mut out).is_err());
+}
+
+#[test]
+fn writer_is_poisoned_after_an_error() {
+    let mut writer = EscapingWriter::new(Full {
+        written: Vec::new(),
+        capacity: 3,
+    });
+    assert!(writer.write(b"a\nb\n").is_err());
+    assert!(writer.write(b"c").is_err());
+    assert_eq!(writer.get_ref().written, b"a\\n");
+}